@@ -0,0 +1,193 @@
+use std::{future::Future, time::Duration};
+
+use crate::{
+  consts,
+  journal::{Journal, JournalBackend},
+  reconnect::{Connector, ReconnectPolicy},
+  serial::{Serial, SerialConfig},
+  transport::{self, BoxedTransport, PortConfig, Transport},
+  ClientError, Concord4,
+};
+
+/// Configuration for a [Concord4] connection.
+///
+/// Every setting starts at the value [Concord4::open] and friends use, so only what needs tuning has to be set.
+///
+/// # example
+/// ```no_run
+/// # use concord4::*;
+/// # use std::time::Duration;
+/// # async fn example() {
+/// let client = Concord4::builder()
+///   .response_timeout(Duration::from_secs(5))
+///   .max_retries(3)
+///   .reconnect(ReconnectPolicy::default())
+///   .connect_tcp("192.168.1.50:4000")
+///   .await
+///   .expect("could not connect to adapter");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Concord4Builder {
+  serial: SerialConfig,
+  port: PortConfig,
+  event_capacity: usize,
+  reconnect: Option<ReconnectPolicy>,
+  journal: Option<Journal>,
+}
+
+impl Default for Concord4Builder {
+  fn default() -> Self {
+    Self {
+      serial: SerialConfig::default(),
+      port: PortConfig::default(),
+      event_capacity: consts::EVENT_CAPACITY,
+      reconnect: None,
+      journal: None,
+    }
+  }
+}
+
+impl Concord4Builder {
+  /// a builder with the default settings
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// how long to wait for the panel to ACK or NAK a message before resending it (default: 2s)
+  ///
+  /// slow serial-over-ip links may need more time
+  pub fn response_timeout(mut self, timeout: Duration) -> Self {
+    self.serial.response_timeout = timeout;
+    self
+  }
+
+  /// how many times a message is resent before giving up on it (default: 5)
+  pub fn max_retries(mut self, retries: u8) -> Self {
+    self.serial.max_retries = retries;
+    self
+  }
+
+  /// whether the client requests the equipment list and dynamic data by itself (default: true)
+  ///
+  /// with this off nothing is sent unless asked for, which keeps scripted tests deterministic
+  pub fn auto_bootstrap(mut self, enabled: bool) -> Self {
+    self.serial.auto_bootstrap = enabled;
+    self
+  }
+
+  /// how long to wait for the panel to ask for an image reset before requesting the equipment list anyway (default: 10s)
+  pub fn bootstrap_delay(mut self, delay: Duration) -> Self {
+    self.serial.bootstrap_delay = delay;
+    self
+  }
+
+  /// how many outgoing messages can wait in line before sending blocks (default: 32)
+  pub fn queue_capacity(mut self, capacity: usize) -> Self {
+    // tokio channels can't be empty
+    self.serial.queue_capacity = capacity.max(1);
+    self
+  }
+
+  /// how many messages a subscriber can fall behind before it starts missing them (default: 256)
+  pub fn event_capacity(mut self, capacity: usize) -> Self {
+    self.event_capacity = capacity.max(1);
+    self
+  }
+
+  /// the baud rate of the serial port, only used by [Concord4Builder::open] (default: 9600)
+  pub fn baud_rate(mut self, baud_rate: u32) -> Self {
+    self.port.baud_rate = baud_rate;
+    self
+  }
+
+  /// the parity of the serial port, only used by [Concord4Builder::open] (default: odd)
+  pub fn parity(mut self, parity: tokio_serial::Parity) -> Self {
+    self.port.parity = parity;
+    self
+  }
+
+  /// whether to clear the serial port's buffers when it is opened, only used by [Concord4Builder::open] (default: true)
+  pub fn clear_buffer_on_open(mut self, clear: bool) -> Self {
+    self.port.clear_buffer = clear;
+    self
+  }
+
+  /// reopen the transport whenever it closes, see [Concord4::with_reconnect] (default: off)
+  pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+    self.reconnect = Some(policy);
+    self
+  }
+
+  /// record alarm/trouble events and arming level changes, see [Concord4::with_journal] (default: off)
+  pub fn journal(mut self, backend: impl JournalBackend) -> Self {
+    self.journal = Some(Journal::new(backend));
+    self
+  }
+
+  /// open a connection over a serial port, see [Concord4::open]
+  pub async fn open(self, path: &str) -> Result<Concord4, ClientError> {
+    let path = path.to_string();
+    let port = self.port.clone();
+
+    self
+      .connect_with(move || {
+        let path = path.clone();
+        let port = port.clone();
+        async move { transport::open_serial(&path, &port) }
+      })
+      .await
+  }
+
+  /// open a connection through a serial-to-ethernet adapter, see [Concord4::connect_tcp]
+  pub async fn connect_tcp<A: tokio::net::ToSocketAddrs>(self, addr: A) -> Result<Concord4, ClientError> {
+    // resolve once so reconnects go back to the same adapter
+    let addrs: Vec<_> = tokio::net::lookup_host(addr)
+      .await
+      .map_err(ClientError::Transport)?
+      .collect();
+
+    self
+      .connect_with(move || {
+        let addrs = addrs.clone();
+        async move { transport::connect_tcp(addrs.as_slice()).await }
+      })
+      .await
+  }
+
+  /// open a connection using a function that opens the transport, see [Concord4::connect_with]
+  pub async fn connect_with<F, Fut, T>(self, connect: F) -> Result<Concord4, ClientError>
+  where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, ClientError>> + Send + 'static,
+    T: Transport + 'static,
+  {
+    let connector: Connector = Box::new(move || {
+      let connecting = connect();
+      Box::pin(async move { Ok(Box::new(connecting.await?) as BoxedTransport) })
+    });
+
+    let transport = connector().await?;
+
+    Ok(self.finish(transport, Some(connector)))
+  }
+
+  /// use an already opened transport, see [Concord4::from_transport]
+  ///
+  /// the transport can't be reopened, so any reconnect policy is ignored
+  pub fn build<T: Transport + 'static>(self, transport: T) -> Concord4 {
+    self.finish(Box::new(transport), None)
+  }
+
+  fn finish(self, transport: BoxedTransport, connector: Option<Connector>) -> Concord4 {
+    let client = Concord4::from_serial(Serial::new(transport, self.serial), connector, self.event_capacity);
+    if let Some(journal) = self.journal {
+      client.handle.set_journal(journal);
+    }
+
+    match self.reconnect {
+      Some(policy) => client.with_reconnect(policy),
+      None => client,
+    }
+  }
+}
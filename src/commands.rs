@@ -1,7 +1,9 @@
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+use crate::{serial::SendOutcome, touchpad::TouchpadDisplay, ArmingLevel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub enum Keypress {
   #[cfg_attr(feature = "json", serde(rename = "0"))]
@@ -61,6 +63,23 @@ pub enum Keypress {
   TPFKey,
 }
 
+impl Keypress {
+  /// the digit keys for entering `number` on a touchpad, padded with leading zeros to at least `width` digits
+  ///
+  /// # example
+  /// ```
+  /// # use concord4::Keypress;
+  /// assert_eq!(Keypress::digits(7, 2), vec![Keypress::Zero, Keypress::Seven]);
+  /// assert_eq!(Keypress::digits(112, 2), vec![Keypress::One, Keypress::One, Keypress::Two]);
+  /// ```
+  pub fn digits(number: u16, width: usize) -> Vec<Keypress> {
+    format!("{:0width$}", number)
+      .bytes()
+      .filter_map(|digit| Keypress::try_from(digit - b'0').ok())
+      .collect()
+  }
+}
+
 impl From<Keypress> for u8 {
   fn from(value: Keypress) -> Self {
     match value {
@@ -110,6 +129,58 @@ impl From<Keypress> for u8 {
   }
 }
 
+impl TryFrom<u8> for Keypress {
+  type Error = ();
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0x00 => Ok(Keypress::Zero),
+      0x01 => Ok(Keypress::One),
+      0x02 => Ok(Keypress::Two),
+      0x03 => Ok(Keypress::Three),
+      0x04 => Ok(Keypress::Four),
+      0x05 => Ok(Keypress::Five),
+      0x06 => Ok(Keypress::Six),
+      0x07 => Ok(Keypress::Seven),
+      0x08 => Ok(Keypress::Eight),
+      0x09 => Ok(Keypress::Nine),
+      0x0a => Ok(Keypress::Star),
+      0x0b => Ok(Keypress::Pound),
+      0x0c => Ok(Keypress::PolicePanic),
+      0x0d => Ok(Keypress::AuxPanic),
+      0x0e => Ok(Keypress::FirePanic),
+      0x10 => Ok(Keypress::LightsOn),
+      0x11 => Ok(Keypress::LightsOff),
+      0x12 => Ok(Keypress::LightsToggle),
+      0x13 => Ok(Keypress::KeyswitchOn),
+      0x14 => Ok(Keypress::KeyswitchOff),
+      0x15 => Ok(Keypress::KeyswitchToggle),
+      0x1c => Ok(Keypress::FireTPAcknowledge),
+      0x1d => Ok(Keypress::FireTPSilence),
+      0x1e => Ok(Keypress::FireTPFireTest),
+      0x1f => Ok(Keypress::FireTPSmokeReset),
+      0x20 => Ok(Keypress::KeyfobDisarm),
+      0x21 => Ok(Keypress::KeyfobArm),
+      0x22 => Ok(Keypress::KeyfobLights),
+      0x23 => Ok(Keypress::KeyfobStar),
+      0x24 => Ok(Keypress::KeyfobArmDisarm),
+      0x25 => Ok(Keypress::KeyfobLightsStar),
+      0x26 => Ok(Keypress::KeyfobLongLights),
+      0x27 => Ok(Keypress::KeyfobDirectArmLevelThree),
+      0x28 => Ok(Keypress::KeyfobDirectArmLevelTwo),
+      0x29 => Ok(Keypress::KeyfobArmStar),
+      0x2a => Ok(Keypress::KeyfobDisarmLights),
+      0x2c => Ok(Keypress::TPAKey),
+      0x30 => Ok(Keypress::TPBKey),
+      0x2d => Ok(Keypress::TPCKey),
+      0x33 => Ok(Keypress::TPDKey),
+      0x2e => Ok(Keypress::TPEKey),
+      0x36 => Ok(Keypress::TPFKey),
+      _ => Err(()),
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
 pub enum ListRequest {
@@ -143,6 +214,23 @@ impl From<u8> for ListRequest {
   }
 }
 
+impl From<ListRequest> for u8 {
+  fn from(value: ListRequest) -> Self {
+    match value {
+      ListRequest::AllData => 0x00,
+      ListRequest::ZoneData => 0x03,
+      ListRequest::PartData => 0x04,
+      ListRequest::BusDevData => 0x05,
+      ListRequest::BusCapData => 0x06,
+      ListRequest::OutputData => 0x07,
+      ListRequest::UserData => 0x09,
+      ListRequest::ScheduleData => 0x0a,
+      ListRequest::EventData => 0x0b,
+      ListRequest::LightAttach => 0x0c,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
 /// The different modes the alarm can be armed to
@@ -153,6 +241,16 @@ pub enum ArmMode {
   Away,
 }
 
+impl ArmMode {
+  /// the arming level the panel reports once a partition is armed in this mode
+  pub fn arming_level(&self) -> ArmingLevel {
+    match self {
+      ArmMode::Stay => ArmingLevel::Home,
+      ArmMode::Away => ArmingLevel::Away,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
 /// The different levels the alarm can be armed to
@@ -188,3 +286,60 @@ pub struct DisarmOptions {
   /// the partition to disarm
   pub partition: Option<u8>,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
+/// The panic alarms a touchpad can raise
+pub enum PanicKind {
+  /// a police (intrusion) panic
+  Police,
+  /// an auxiliary (usually medical) panic
+  Auxiliary,
+  /// a fire panic
+  Fire,
+}
+
+impl From<PanicKind> for Keypress {
+  fn from(value: PanicKind) -> Self {
+    match value {
+      PanicKind::Police => Keypress::PolicePanic,
+      PanicKind::Auxiliary => Keypress::AuxPanic,
+      PanicKind::Fire => Keypress::FirePanic,
+    }
+  }
+}
+
+#[derive(Debug)]
+/// Proof that the caller really means to trigger a panic alarm, see [crate::Concord4Handle::trigger_panic]
+///
+/// a panic is reported to the central station, which can send the police, fire department or an ambulance, \
+/// so it takes one of these, naming the same panic, and each one can only be used once
+pub struct PanicConfirmation {
+  kind: PanicKind,
+}
+
+impl PanicConfirmation {
+  /// confirm that a `kind` panic should be triggered
+  pub fn confirm(kind: PanicKind) -> Self {
+    Self { kind }
+  }
+
+  pub(crate) fn kind(&self) -> PanicKind {
+    self.kind
+  }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+/// The result of a command that waits for the panel to report the change, \
+/// like an arm or disarm changing the partition's arming level
+pub enum ConfirmOutcome<T = ArmingLevel> {
+  /// the panel reported the expected state, e.g. the new arming level or zone status
+  Confirmed(T),
+  /// the panel refused the keypresses on the partition's touchpad (e.g. "INVALID CODE")
+  Rejected(TouchpadDisplay),
+  /// the keypresses never reached the panel
+  NotDelivered(SendOutcome),
+  /// the panel accepted the keypresses but did not report the change in time
+  Timeout,
+}
@@ -6,11 +6,12 @@ use serde::{Deserialize, Serialize};
 use crate::{
   commands::{Keypress, ListRequest},
   equipment::{
-    AlarmTrouble, ArmingLevelData, FeatureState, PanelData, PartitionData, SirenStop, SuperBusDeviceCapability,
-    SuperBusDeviceData, TimeDate, UserData, ZoneData, ZoneStatusData,
+    AlarmTrouble, ArmingLevelData, EntryExitDelay, FeatureState, LightAttach, LightsState, PanelData, PartitionData,
+    SirenSetup, SirenStop, SuperBusDeviceCapability, SuperBusDeviceData, TimeDate, UserData, UserLights, ZoneData,
+    ZoneStatusData,
   },
   touchpad::TouchpadDisplay,
-  ArmOptions, DisarmOptions,
+  ArmOptions, DecodeError, DisarmOptions,
 };
 
 #[derive(Debug, Clone)]
@@ -56,6 +57,42 @@ pub enum SendableMessage {
   DynamicDataRefresh,
 }
 
+impl TryFrom<Vec<u8>> for SendableMessage {
+  type Error = DecodeError;
+
+  fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+    match value.first() {
+      Some(0x02) => Ok(SendableMessage::List(
+        value
+          .get(1)
+          .copied()
+          .map(ListRequest::from)
+          .unwrap_or(ListRequest::AllData),
+      )),
+      Some(0x20) => Ok(SendableMessage::DynamicDataRefresh),
+      Some(0x40) if value.len() > 3 => {
+        let keys = value[3..]
+          .iter()
+          .copied()
+          .map(|key| {
+            Keypress::try_from(key).map_err(|_| DecodeError::InvalidValue {
+              field: "keypress",
+              value: key,
+            })
+          })
+          .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SendableMessage::Keypress(value[1], keys))
+      }
+      Some(&command) => Err(DecodeError::UnknownCommand {
+        command,
+        subcommand: value.get(1).copied(),
+      }),
+      None => Err(DecodeError::ShortFrame { expected: 1, actual: 0 }),
+    }
+  }
+}
+
 #[derive(Clone)]
 #[cfg_attr(
   feature = "json",
@@ -76,13 +113,13 @@ pub enum RecvMessage {
   UserData(UserData),
   SchedData(Vec<u8>),
   SchedEventData(Vec<u8>),
-  LightAttach(Vec<u8>),
+  LightAttach(LightAttach),
   ClearImage(Vec<u8>),
   ZoneStatus(ZoneStatusData),
   ArmingLevel(ArmingLevelData),
   AlarmTrouble(AlarmTrouble),
-  EntryExitDelay(Vec<u8>),
-  SirenSetup(Vec<u8>),
+  EntryExitDelay(EntryExitDelay),
+  SirenSetup(SirenSetup),
   SirenSync,
   SirenGo,
   Touchpad(TouchpadDisplay),
@@ -90,9 +127,22 @@ pub enum RecvMessage {
   FeatState(FeatureState),
   Temp(Vec<u8>),
   TimeAndDate(TimeDate),
-  LightsState(Vec<u8>),
-  UserLights(Vec<u8>),
+  LightsState(LightsState),
+  UserLights(UserLights),
   Keyfob(Vec<u8>),
+  /// a well-formed message with a command this library doesn't understand yet
+  Unknown {
+    command: u8,
+    subcommand: Option<u8>,
+    payload: Vec<u8>,
+  },
+  /// a frame that was garbled on the wire or didn't match the layout of its command
+  ///
+  /// `raw` is the frame exactly as it was received, without the leading line feed
+  Corrupt {
+    raw: Vec<u8>,
+    reason: DecodeError,
+  },
 }
 
 #[cfg(feature = "json")]
@@ -135,39 +185,57 @@ impl std::fmt::Debug for RecvMessage {
       RecvMessage::LightsState(data) => write!(f, "Lights State Command: {:?}", data),
       RecvMessage::UserLights(data) => write!(f, "User Lights Command: {:?}", data),
       RecvMessage::Keyfob(data) => write!(f, "Keyfob Command: {:?}", data),
+      RecvMessage::Unknown {
+        command,
+        subcommand,
+        payload,
+      } => write!(
+        f,
+        "Unknown Command {:#04x} (subcommand {:?}): {:?}",
+        command, subcommand, payload
+      ),
+      RecvMessage::Corrupt { raw, reason } => write!(f, "Corrupt Frame ({}): {:?}", reason, raw),
     }
   }
 }
 
 impl TryFrom<Vec<u8>> for RecvMessage {
-  type Error = ();
+  type Error = DecodeError;
 
   fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-    let cmd = value[0];
-    let subcmd = if value.len() > 1 { Some(value[1]) } else { None };
+    let Some(&cmd) = value.first() else {
+      return Err(DecodeError::ShortFrame { expected: 1, actual: 0 });
+    };
+    let subcmd = value.get(1).copied();
 
     if let Some(subcmd) = subcmd {
       let data = value[2..].to_vec();
 
       let message = match (cmd, subcmd) {
-        (0x22, 0x01) => Ok(RecvMessage::ArmingLevel(ArmingLevelData::from(data))),
-        (0x22, 0x02) => Ok(RecvMessage::AlarmTrouble(AlarmTrouble::from(data))),
-        (0x22, 0x03) => Ok(RecvMessage::EntryExitDelay(data)),
-        (0x22, 0x04) => Ok(RecvMessage::SirenSetup(data)),
-        (0x22, 0x05) => Ok(RecvMessage::SirenSync),
-        (0x22, 0x06) => Ok(RecvMessage::SirenGo),
-        (0x22, 0x09) => Ok(RecvMessage::Touchpad(TouchpadDisplay::from(data))),
-        (0x22, 0x0b) => Ok(RecvMessage::SirenStop(SirenStop::from(data))),
-        (0x22, 0x0c) => Ok(RecvMessage::FeatState(FeatureState::from(data))),
-        (0x22, 0x0d) => Ok(RecvMessage::Temp(data)),
-        (0x22, 0x0e) => Ok(RecvMessage::TimeAndDate(TimeDate::from(data))),
-        (0x23, 0x01) => Ok(RecvMessage::LightsState(data)),
-        (0x23, 0x02) => Ok(RecvMessage::UserLights(data)),
-        (0x23, 0x03) => Ok(RecvMessage::Keyfob(data)),
-        _ => Err(()),
+        (0x22, 0x01) => Some(ArmingLevelData::try_from(data).map(RecvMessage::ArmingLevel)),
+        (0x22, 0x02) => Some(AlarmTrouble::try_from(data).map(RecvMessage::AlarmTrouble)),
+        (0x22, 0x03) => Some(EntryExitDelay::try_from(data).map(RecvMessage::EntryExitDelay)),
+        (0x22, 0x04) => Some(SirenSetup::try_from(data).map(RecvMessage::SirenSetup)),
+        (0x22, 0x05) => Some(Ok(RecvMessage::SirenSync)),
+        (0x22, 0x06) => Some(Ok(RecvMessage::SirenGo)),
+        (0x22, 0x09) => Some(TouchpadDisplay::try_from(data).map(RecvMessage::Touchpad)),
+        (0x22, 0x0b) => Some(SirenStop::try_from(data).map(RecvMessage::SirenStop)),
+        (0x22, 0x0c) => Some(FeatureState::try_from(data).map(RecvMessage::FeatState)),
+        (0x22, 0x0d) => Some(Ok(RecvMessage::Temp(data))),
+        (0x22, 0x0e) => Some(TimeDate::try_from(data).map(RecvMessage::TimeAndDate)),
+        (0x23, 0x01) => Some(LightsState::try_from(data).map(RecvMessage::LightsState)),
+        (0x23, 0x02) => Some(UserLights::try_from(data).map(RecvMessage::UserLights)),
+        (0x23, 0x03) => Some(Ok(RecvMessage::Keyfob(data))),
+        (0x22 | 0x23, _) => Some(Ok(RecvMessage::Unknown {
+          command: cmd,
+          subcommand: Some(subcmd),
+          payload: data,
+        })),
+        // commands without a subcommand fall through to the match below
+        _ => None,
       };
 
-      if message.is_ok() {
+      if let Some(message) = message {
         return message;
       }
     }
@@ -175,22 +243,77 @@ impl TryFrom<Vec<u8>> for RecvMessage {
     let data = value[1..].to_vec();
 
     match cmd {
-      0x01 => Ok(RecvMessage::PanelType(PanelData::from(data))),
+      0x01 => PanelData::try_from(data).map(RecvMessage::PanelType),
       0x02 => Ok(RecvMessage::AutomationEventLost(data)),
-      0x03 => Ok(RecvMessage::ZoneData(ZoneData::from(data))),
-      0x04 => Ok(RecvMessage::PartitionData(PartitionData::from(data))),
-      0x05 => Ok(RecvMessage::SuperBusDevData(SuperBusDeviceData::from(data))),
-      0x06 => Ok(RecvMessage::SuperBusDevCap(SuperBusDeviceCapability::from(data))),
+      0x03 => ZoneData::try_from(data).map(RecvMessage::ZoneData),
+      0x04 => PartitionData::try_from(data).map(RecvMessage::PartitionData),
+      0x05 => SuperBusDeviceData::try_from(data).map(RecvMessage::SuperBusDevData),
+      0x06 => SuperBusDeviceCapability::try_from(data).map(RecvMessage::SuperBusDevCap),
       0x07 => Ok(RecvMessage::OutputData(data)),
       0x08 => Ok(RecvMessage::EqptListDone),
-      0x09 => Ok(RecvMessage::UserData(UserData::from(data))),
+      0x09 => UserData::try_from(data).map(RecvMessage::UserData),
       0x0a => Ok(RecvMessage::SchedData(data)),
       0x0b => Ok(RecvMessage::SchedEventData(data)),
-      0x0c => Ok(RecvMessage::LightAttach(data)),
+      0x0c => LightAttach::try_from(data).map(RecvMessage::LightAttach),
       0x20 => Ok(RecvMessage::ClearImage(data)),
-      0x21 => Ok(RecvMessage::ZoneStatus(ZoneStatusData::from(data))),
-      0x23 => Ok(RecvMessage::LightsState(data)),
-      _ => Err(()),
+      0x21 => ZoneStatusData::try_from(data).map(RecvMessage::ZoneStatus),
+      _ => Ok(RecvMessage::Unknown {
+        command: cmd,
+        subcommand: None,
+        payload: data,
+      }),
     }
   }
 }
+
+impl TryFrom<RecvMessage> for Vec<u8> {
+  type Error = ();
+
+  fn try_from(value: RecvMessage) -> Result<Self, Self::Error> {
+    let (command, data): (&[u8], Vec<u8>) = match value {
+      // ACK and NAK are bare control characters rather than messages, and a corrupt frame never was one
+      RecvMessage::Ack | RecvMessage::Nak | RecvMessage::Corrupt { .. } => return Err(()),
+      RecvMessage::PanelType(data) => (&[0x01], data.into()),
+      RecvMessage::AutomationEventLost(data) => (&[0x02], data),
+      RecvMessage::ZoneData(data) => (&[0x03], data.into()),
+      RecvMessage::PartitionData(data) => (&[0x04], data.into()),
+      RecvMessage::SuperBusDevData(data) => (&[0x05], data.into()),
+      RecvMessage::SuperBusDevCap(data) => (&[0x06], data.into()),
+      RecvMessage::OutputData(data) => (&[0x07], data),
+      RecvMessage::EqptListDone => (&[0x08], vec![]),
+      RecvMessage::UserData(data) => (&[0x09], data.into()),
+      RecvMessage::SchedData(data) => (&[0x0a], data),
+      RecvMessage::SchedEventData(data) => (&[0x0b], data),
+      RecvMessage::LightAttach(data) => (&[0x0c], data.into()),
+      RecvMessage::ClearImage(data) => (&[0x20], data),
+      RecvMessage::ZoneStatus(data) => (&[0x21], data.into()),
+      RecvMessage::ArmingLevel(data) => (&[0x22, 0x01], data.into()),
+      RecvMessage::AlarmTrouble(data) => (&[0x22, 0x02], data.into()),
+      RecvMessage::EntryExitDelay(data) => (&[0x22, 0x03], data.into()),
+      RecvMessage::SirenSetup(data) => (&[0x22, 0x04], data.into()),
+      RecvMessage::SirenSync => (&[0x22, 0x05], vec![]),
+      RecvMessage::SirenGo => (&[0x22, 0x06], vec![]),
+      RecvMessage::Touchpad(data) => (&[0x22, 0x09], data.into()),
+      RecvMessage::SirenStop(data) => (&[0x22, 0x0b], data.into()),
+      RecvMessage::FeatState(data) => (&[0x22, 0x0c], data.into()),
+      RecvMessage::Temp(data) => (&[0x22, 0x0d], data),
+      RecvMessage::TimeAndDate(data) => (&[0x22, 0x0e], data.into()),
+      RecvMessage::LightsState(data) => (&[0x23, 0x01], data.into()),
+      RecvMessage::UserLights(data) => (&[0x23, 0x02], data.into()),
+      RecvMessage::Keyfob(data) => (&[0x23, 0x03], data),
+      RecvMessage::Unknown {
+        command,
+        subcommand,
+        payload,
+      } => {
+        let mut data = vec![command];
+        data.extend(subcommand);
+        data.extend(payload);
+
+        return Ok(data);
+      }
+    };
+
+    Ok([command, &data].concat())
+  }
+}
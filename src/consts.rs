@@ -1,7 +1,21 @@
+use std::time::Duration;
+
 pub const BAUD_RATE: u32 = 9600;
 pub const DATA_BITS: tokio_serial::DataBits = tokio_serial::DataBits::Eight;
 pub const PARITY: tokio_serial::Parity = tokio_serial::Parity::Odd;
 pub const ASCII_BYTE_REAL_LEN: usize = 2;
+/// how many messages a subscriber can fall behind before it starts missing them
+pub const EVENT_CAPACITY: usize = 256;
+/// how many outgoing messages can wait in line for the panel
+pub const QUEUE_CAPACITY: usize = 32;
+/// how many journal entries can wait to be written before new ones are dropped
+pub const JOURNAL_QUEUE_CAPACITY: usize = 1024;
+/// how long to wait for an ACK/NAK before resending
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+/// how many times a message is resent before giving up
+pub const MAX_RETRIES: u8 = 5;
+/// how long to wait for the panel to ask for an image reset before requesting the equipment list anyway
+pub const BOOTSTRAP_DELAY: Duration = Duration::from_secs(10);
 
 pub const ACK: u8 = 0x06;
 pub const NAK: u8 = 0x15;
@@ -11,12 +25,14 @@ pub enum CtrlFlow {
   Nak,
 }
 
-impl From<u8> for CtrlFlow {
-  fn from(b: u8) -> Self {
+impl TryFrom<u8> for CtrlFlow {
+  type Error = ();
+
+  fn try_from(b: u8) -> Result<Self, Self::Error> {
     match b {
-      ACK => CtrlFlow::Ack,
-      NAK => CtrlFlow::Nak,
-      _ => panic!("not a u8 control character"),
+      ACK => Ok(CtrlFlow::Ack),
+      NAK => Ok(CtrlFlow::Nak),
+      _ => Err(()),
     }
   }
 }
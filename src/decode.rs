@@ -238,7 +238,8 @@ pub fn decode_text_tokens(tokens: &[u8]) -> String {
 
   for (i, t) in tokens.iter().enumerate() {
     if *t == 0xFD {
-      s = s[..s.len() - 1].to_string();
+      // a backspace at the start of the display has nothing to erase
+      s.pop();
       continue;
     }
 
@@ -253,6 +254,46 @@ pub fn decode_text_tokens(tokens: &[u8]) -> String {
   s
 }
 
+/// the inverse of [decode_text_tokens]: whole words are matched against word tokens, \
+/// anything else is spelled out one character at a time
+pub fn encode_text_tokens(text: &str) -> Vec<u8> {
+  let words: Vec<&str> = text.split(' ').collect();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+
+  while i < words.len() {
+    // some tokens span two words ("AC POWER", "ENERGY SAVER", "IN SERVICE")
+    if let Some(token) = words.get(i..i + 2).and_then(|pair| word_token(&pair.join(" "))) {
+      tokens.push(token);
+      i += 2;
+      continue;
+    }
+
+    // word tokens get their trailing space added back by the decoder
+    if let Some(token) = word_token(words[i]) {
+      tokens.push(token);
+    } else {
+      tokens.extend(words[i].chars().filter_map(|c| char_token(&c.to_string())));
+
+      if i < words.len() - 1 {
+        tokens.push(0x2B);
+      }
+    }
+
+    i += 1;
+  }
+
+  tokens
+}
+
+fn word_token(word: &str) -> Option<u8> {
+  (0x30..=0xD8).find(|t| TextToken(*t).as_str() == word)
+}
+
+fn char_token(c: &str) -> Option<u8> {
+  (0x0..=0x2F).find(|t| TextToken(*t).as_str() == c)
+}
+
 pub fn letter_from_representative_hex(hex: u8) -> char {
   match hex {
     0x01 => 'A',
@@ -284,3 +325,9 @@ pub fn letter_from_representative_hex(hex: u8) -> char {
     _ => ' ',
   }
 }
+
+pub fn representative_hex_from_letter(letter: char) -> u8 {
+  (0x01..=0x26)
+    .find(|hex| letter_from_representative_hex(*hex) == letter)
+    .unwrap_or(0x00)
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::decode;
+use crate::{decode, DecodeError};
 
 #[cfg(feature = "json")]
 use serde::Serialize;
@@ -27,15 +27,100 @@ pub enum ZoneStatus {
 
 impl From<u8> for ZoneStatus {
   fn from(data: u8) -> Self {
-    match data {
-      0x0 => ZoneStatus::Normal,
-      0x1 => ZoneStatus::Tripped,
-      0x2 => ZoneStatus::Faulted,
-      0x4 => ZoneStatus::Alarm,
-      0x8 => ZoneStatus::Trouble,
-      0xA => ZoneStatus::Bypassed,
-      _ => ZoneStatus::Unknown,
+    ZoneStatusFlags::from(data).primary()
+  }
+}
+
+impl From<ZoneStatus> for u8 {
+  fn from(value: ZoneStatus) -> Self {
+    ZoneStatusFlags::from(value).into()
+  }
+}
+
+/// The zone state byte, which is a bitmask: a zone can be tripped and bypassed, or in alarm and trouble, at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct ZoneStatusFlags {
+  pub tripped: bool,
+  pub faulted: bool,
+  pub alarm: bool,
+  pub trouble: bool,
+  pub bypassed: bool,
+}
+
+impl ZoneStatusFlags {
+  const TRIPPED: u8 = 0x01;
+  const FAULTED: u8 = 0x02;
+  const ALARM: u8 = 0x04;
+  const TROUBLE: u8 = 0x08;
+  const BYPASSED: u8 = 0x10;
+
+  /// whether none of the flags are set
+  pub fn is_normal(&self) -> bool {
+    *self == Self::default()
+  }
+
+  /// the single most important status, for when a zone has to be summed up in one word
+  ///
+  /// alarm beats trouble beats bypassed beats faulted beats tripped
+  pub fn primary(&self) -> ZoneStatus {
+    if self.alarm {
+      ZoneStatus::Alarm
+    } else if self.trouble {
+      ZoneStatus::Trouble
+    } else if self.bypassed {
+      ZoneStatus::Bypassed
+    } else if self.faulted {
+      ZoneStatus::Faulted
+    } else if self.tripped {
+      ZoneStatus::Tripped
+    } else {
+      ZoneStatus::Normal
+    }
+  }
+}
+
+impl From<u8> for ZoneStatusFlags {
+  fn from(data: u8) -> Self {
+    ZoneStatusFlags {
+      tripped: data & Self::TRIPPED != 0,
+      faulted: data & Self::FAULTED != 0,
+      alarm: data & Self::ALARM != 0,
+      trouble: data & Self::TROUBLE != 0,
+      bypassed: data & Self::BYPASSED != 0,
+    }
+  }
+}
+
+impl From<ZoneStatusFlags> for u8 {
+  fn from(value: ZoneStatusFlags) -> Self {
+    [
+      (value.tripped, ZoneStatusFlags::TRIPPED),
+      (value.faulted, ZoneStatusFlags::FAULTED),
+      (value.alarm, ZoneStatusFlags::ALARM),
+      (value.trouble, ZoneStatusFlags::TROUBLE),
+      (value.bypassed, ZoneStatusFlags::BYPASSED),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .fold(0x0, |data, (_, bit)| data | bit)
+  }
+}
+
+impl From<ZoneStatus> for ZoneStatusFlags {
+  fn from(value: ZoneStatus) -> Self {
+    let mut flags = ZoneStatusFlags::default();
+
+    match value {
+      ZoneStatus::Normal | ZoneStatus::Unknown => {}
+      ZoneStatus::Tripped => flags.tripped = true,
+      ZoneStatus::Faulted => flags.faulted = true,
+      ZoneStatus::Alarm => flags.alarm = true,
+      ZoneStatus::Trouble => flags.trouble = true,
+      ZoneStatus::Bypassed => flags.bypassed = true,
     }
+
+    flags
   }
 }
 
@@ -58,15 +143,25 @@ impl From<u8> for ZoneType {
   }
 }
 
+impl From<ZoneType> for u8 {
+  fn from(value: ZoneType) -> Self {
+    match value {
+      ZoneType::Hardwired => 0x0,
+      ZoneType::RF => 0x1,
+      ZoneType::Touchpad => 0x2,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct ZoneData {
   pub partition_number: u8,
   pub area_number: u8,
   pub group_number: u8,
-  pub zone_number: u8,
+  pub zone_number: u16,
   pub zone_type: ZoneType,
-  pub zone_status: ZoneStatus,
+  pub zone_status: ZoneStatusFlags,
   pub zone_text: String,
 }
 
@@ -74,6 +169,11 @@ impl ZoneData {
   pub fn group_id(&self) -> String {
     format!("p{}-g{}", self.partition_number, self.group_number)
   }
+
+  /// the most important of the zone's status flags, see [ZoneStatusFlags::primary]
+  pub fn status(&self) -> ZoneStatus {
+    self.zone_status.primary()
+  }
 }
 
 impl StringIdentifiable for ZoneData {
@@ -82,17 +182,39 @@ impl StringIdentifiable for ZoneData {
   }
 }
 
-impl From<Vec<u8>> for ZoneData {
-  fn from(data: Vec<u8>) -> Self {
-    ZoneData {
+impl TryFrom<Vec<u8>> for ZoneData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 7)?;
+
+    Ok(ZoneData {
       partition_number: data[0],
       area_number: data[1],
       group_number: data[2],
-      zone_number: data[4],
+      zone_number: u16::from_be_bytes([data[3], data[4]]),
       zone_type: ZoneType::from(data[5]),
-      zone_status: ZoneStatus::from(data[6]),
+      zone_status: ZoneStatusFlags::from(data[6]),
       zone_text: decode::decode_text_tokens(&data[7..]),
-    }
+    })
+  }
+}
+
+impl From<ZoneData> for Vec<u8> {
+  fn from(value: ZoneData) -> Self {
+    let [zone_high, zone_low] = value.zone_number.to_be_bytes();
+    let mut data = vec![
+      value.partition_number,
+      value.area_number,
+      value.group_number,
+      zone_high,
+      zone_low,
+      value.zone_type.into(),
+      value.zone_status.into(),
+    ];
+    data.extend(decode::encode_text_tokens(&value.zone_text));
+
+    data
   }
 }
 
@@ -101,24 +223,47 @@ impl From<Vec<u8>> for ZoneData {
 pub struct ZoneStatusData {
   pub partition_number: u8,
   pub area_number: u8,
-  pub zone_number: u8,
-  pub zone_status: ZoneStatus,
+  pub zone_number: u16,
+  pub zone_status: ZoneStatusFlags,
 }
 
 impl ZoneStatusData {
   pub fn zone_id(&self) -> String {
     format!("p{}-z{}", self.partition_number, self.zone_number)
   }
+
+  /// the most important of the zone's status flags, see [ZoneStatusFlags::primary]
+  pub fn status(&self) -> ZoneStatus {
+    self.zone_status.primary()
+  }
 }
 
-impl From<Vec<u8>> for ZoneStatusData {
-  fn from(data: Vec<u8>) -> Self {
-    ZoneStatusData {
+impl TryFrom<Vec<u8>> for ZoneStatusData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 5)?;
+
+    Ok(ZoneStatusData {
       partition_number: data[0],
       area_number: data[1],
-      zone_number: data[3],
-      zone_status: ZoneStatus::from(data[4]),
-    }
+      zone_number: u16::from_be_bytes([data[2], data[3]]),
+      zone_status: ZoneStatusFlags::from(data[4]),
+    })
+  }
+}
+
+impl From<ZoneStatusData> for Vec<u8> {
+  fn from(value: ZoneStatusData) -> Self {
+    let [zone_high, zone_low] = value.zone_number.to_be_bytes();
+
+    vec![
+      value.partition_number,
+      value.area_number,
+      zone_high,
+      zone_low,
+      value.zone_status.into(),
+    ]
   }
 }
 
@@ -152,6 +297,10 @@ pub struct PartitionData {
   pub area_number: u8,
   pub arming_level: ArmingLevel,
   pub zones: HashSet<String>,
+  /// the features turned on for this partition, as last reported by the panel
+  pub features: HashSet<Feature>,
+  /// who last changed the arming level, as reported by the panel
+  pub last_armed_by: Option<CodeType>,
 }
 
 impl IntIdentifiable for PartitionData {
@@ -160,14 +309,26 @@ impl IntIdentifiable for PartitionData {
   }
 }
 
-impl From<Vec<u8>> for PartitionData {
-  fn from(data: Vec<u8>) -> Self {
-    PartitionData {
+impl TryFrom<Vec<u8>> for PartitionData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 3)?;
+
+    Ok(PartitionData {
       partition_number: data[0],
       area_number: data[1],
       arming_level: ArmingLevel::from(PartitionArmingLevel::from(data[2])),
       zones: HashSet::new(),
-    }
+      features: HashSet::new(),
+      last_armed_by: None,
+    })
+  }
+}
+
+impl From<PartitionData> for Vec<u8> {
+  fn from(value: PartitionData) -> Self {
+    vec![value.partition_number, value.area_number, value.arming_level.into()]
   }
 }
 
@@ -192,6 +353,17 @@ impl From<u8> for PanelType {
   }
 }
 
+impl From<PanelType> for u8 {
+  fn from(value: PanelType) -> Self {
+    match value {
+      PanelType::Concord => 0x14,
+      PanelType::ConcordExpress => 0x0b,
+      PanelType::ConcordExpress4 => 0x1e,
+      PanelType::ConcordEuro => 0x0e,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct PanelData {
@@ -212,14 +384,38 @@ impl Default for PanelData {
   }
 }
 
-impl From<Vec<u8>> for PanelData {
-  fn from(data: Vec<u8>) -> Self {
-    PanelData {
+impl TryFrom<Vec<u8>> for PanelData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 9)?;
+
+    Ok(PanelData {
       panel_type: PanelType::from(data[0]),
       hardware_revision: format!("{}{:X}", decode::letter_from_representative_hex(data[1]), data[2]),
       software_revision: format!("{:X}{:X}", data[3], data[4]),
       serial_number: format!("{:X}{:X}{:X}{:X}", data[5], data[6], data[7], data[8]),
-    }
+    })
+  }
+}
+
+// the revisions and serial number are decoded without padding each byte, so a byte below 0x10 after the first \
+// can't be told apart from its neighbours and comes back shifted into them
+impl From<PanelData> for Vec<u8> {
+  fn from(value: PanelData) -> Self {
+    let mut hardware_revision = value.hardware_revision.chars();
+    let hardware_letter = hardware_revision
+      .next()
+      .map_or(0x0, decode::representative_hex_from_letter);
+    let hardware_number = u8::from_str_radix(hardware_revision.as_str(), 16).unwrap_or_default();
+    let software_revision = u16::from_str_radix(&value.software_revision, 16).unwrap_or_default();
+    let serial_number = u32::from_str_radix(&value.serial_number, 16).unwrap_or_default();
+
+    let mut data = vec![value.panel_type.into(), hardware_letter, hardware_number];
+    data.extend(software_revision.to_be_bytes());
+    data.extend(serial_number.to_be_bytes());
+
+    data
   }
 }
 
@@ -248,6 +444,19 @@ impl From<u8> for ArmingLevel {
   }
 }
 
+impl From<ArmingLevel> for u8 {
+  fn from(value: ArmingLevel) -> Self {
+    match value {
+      ArmingLevel::ZoneTest => 0x0,
+      ArmingLevel::Off => 0x1,
+      ArmingLevel::Home => 0x2,
+      ArmingLevel::Away => 0x3,
+      ArmingLevel::Night => 0x4,
+      ArmingLevel::Silent => 0x5,
+    }
+  }
+}
+
 impl From<PartitionArmingLevel> for ArmingLevel {
   fn from(value: PartitionArmingLevel) -> Self {
     match value {
@@ -265,20 +474,41 @@ impl From<PartitionArmingLevel> for ArmingLevel {
 pub struct ArmingLevelData {
   pub partition_number: u8,
   pub area_number: u8,
+  /// the code, keyfob or other source that changed the arming level
+  pub user: CodeType,
   pub arming_level: ArmingLevel,
 }
 
-impl From<Vec<u8>> for ArmingLevelData {
-  fn from(data: Vec<u8>) -> Self {
-    ArmingLevelData {
+impl TryFrom<Vec<u8>> for ArmingLevelData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 5)?;
+
+    Ok(ArmingLevelData {
       partition_number: data[0],
       area_number: data[1],
+      user: CodeType::from(u16::from_be_bytes([data[2], data[3]])),
       arming_level: ArmingLevel::from(data[4]),
-    }
+    })
   }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl From<ArmingLevelData> for Vec<u8> {
+  fn from(value: ArmingLevelData) -> Self {
+    let [user_high, user_low] = u16::from(value.user).to_be_bytes();
+
+    vec![
+      value.partition_number,
+      value.area_number,
+      user_high,
+      user_low,
+      value.arming_level.into(),
+    ]
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum Feature {
   Chime,
@@ -289,16 +519,53 @@ pub enum Feature {
   QuickArm,
 }
 
-impl From<u8> for Feature {
-  fn from(value: u8) -> Self {
+impl Feature {
+  pub const ALL: [Feature; 6] = [
+    Feature::Chime,
+    Feature::EnergySaver,
+    Feature::NoDelay,
+    Feature::LatchKey,
+    Feature::SilentArm,
+    Feature::QuickArm,
+  ];
+
+  /// every feature whose bit is set in the feature state byte
+  pub fn from_bits(bits: u8) -> HashSet<Feature> {
+    Feature::ALL
+      .into_iter()
+      .filter(|feature| bits & u8::from(*feature) != 0)
+      .collect()
+  }
+
+  /// the feature state byte with a bit set for each of `features`
+  pub fn to_bits<'a>(features: impl IntoIterator<Item = &'a Feature>) -> u8 {
+    features
+      .into_iter()
+      .fold(0x0, |bits, feature| bits | u8::from(*feature))
+  }
+}
+
+/// matches a single feature's bit; use [Feature::from_bits] for the whole feature state byte
+impl TryFrom<u8> for Feature {
+  type Error = ();
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    Feature::ALL
+      .into_iter()
+      .find(|feature| u8::from(*feature) == value)
+      .ok_or(())
+  }
+}
+
+impl From<Feature> for u8 {
+  fn from(value: Feature) -> Self {
     match value {
-      0x01 => Feature::Chime,
-      0x02 => Feature::EnergySaver,
-      0x04 => Feature::NoDelay,
-      0x08 => Feature::LatchKey,
-      0x10 => Feature::SilentArm,
-      0x20 => Feature::QuickArm,
-      _ => Feature::Chime,
+      Feature::Chime => 0x01,
+      Feature::EnergySaver => 0x02,
+      Feature::NoDelay => 0x04,
+      Feature::LatchKey => 0x08,
+      Feature::SilentArm => 0x10,
+      Feature::QuickArm => 0x20,
     }
   }
 }
@@ -308,19 +575,179 @@ impl From<u8> for Feature {
 pub struct FeatureState {
   pub partition_number: u8,
   pub area_number: u8,
-  pub feature_state: Feature,
+  /// every feature that is currently turned on
+  pub features: HashSet<Feature>,
 }
 
-impl From<Vec<u8>> for FeatureState {
-  fn from(data: Vec<u8>) -> Self {
-    FeatureState {
+impl TryFrom<Vec<u8>> for FeatureState {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 3)?;
+
+    Ok(FeatureState {
       partition_number: data[0],
       area_number: data[1],
-      feature_state: Feature::from(data[2]),
+      features: Feature::from_bits(data[2]),
+    })
+  }
+}
+
+impl From<FeatureState> for Vec<u8> {
+  fn from(value: FeatureState) -> Self {
+    vec![
+      value.partition_number,
+      value.area_number,
+      Feature::to_bits(&value.features),
+    ]
+  }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct LightsState {
+  pub partition_number: u8,
+  pub area_number: u8,
+  /// every light (1-9) that is currently on
+  pub lights: HashSet<u8>,
+}
+
+impl LightsState {
+  /// the lights a partition can control, from the lights key followed by a digit
+  pub const LIGHTS: [u8; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+  /// every light whose bit is set in a lights state mask
+  pub fn from_bits(bits: u16) -> HashSet<u8> {
+    LightsState::LIGHTS
+      .into_iter()
+      .filter(|light| bits & (1 << light) != 0)
+      .collect()
+  }
+
+  /// the lights state mask with a bit set for each of `lights`
+  pub fn to_bits<'a>(lights: impl IntoIterator<Item = &'a u8>) -> u16 {
+    let bits = lights
+      .into_iter()
+      .filter(|light| LightsState::LIGHTS.contains(light))
+      .fold(0x0, |bits, light| bits | (1 << light));
+
+    // bit 0 stands for all the lights being on
+    if LightsState::LIGHTS.iter().all(|light| bits & (1 << light) != 0) {
+      bits | 0x1
+    } else {
+      bits
     }
   }
 }
 
+impl TryFrom<Vec<u8>> for LightsState {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 4)?;
+
+    Ok(LightsState {
+      partition_number: data[0],
+      area_number: data[1],
+      lights: LightsState::from_bits(u16::from_be_bytes([data[2], data[3]])),
+    })
+  }
+}
+
+impl From<LightsState> for Vec<u8> {
+  fn from(value: LightsState) -> Self {
+    let mut data = vec![value.partition_number, value.area_number];
+    data.extend(LightsState::to_bits(&value.lights).to_be_bytes());
+
+    data
+  }
+}
+
+/// A light turned on or off from a touchpad.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct UserLights {
+  pub partition_number: u8,
+  pub area_number: u8,
+  /// the light (1-9) that changed, or 0 for all of them
+  pub light_number: u8,
+  pub on: bool,
+}
+
+impl TryFrom<Vec<u8>> for UserLights {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 4)?;
+
+    Ok(UserLights {
+      partition_number: data[0],
+      area_number: data[1],
+      light_number: data[2],
+      on: data[3] != 0,
+    })
+  }
+}
+
+impl From<UserLights> for Vec<u8> {
+  fn from(value: UserLights) -> Self {
+    vec![
+      value.partition_number,
+      value.area_number,
+      value.light_number,
+      value.on.into(),
+    ]
+  }
+}
+
+/// A light attached to a sensor, so the light turns on when the zone trips, from the equipment list.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct LightAttach {
+  pub partition_number: u8,
+  pub area_number: u8,
+  /// the light (1-9) that is attached
+  pub light_number: u8,
+  /// the zone (sensor) the light is attached to
+  pub zone_number: u16,
+}
+
+impl LightAttach {
+  /// the id of the attached zone in [crate::ConcordStateInner::zones]
+  pub fn zone_id(&self) -> String {
+    format!("p{}-z{}", self.partition_number, self.zone_number)
+  }
+}
+
+impl TryFrom<Vec<u8>> for LightAttach {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 5)?;
+
+    Ok(LightAttach {
+      partition_number: data[0],
+      area_number: data[1],
+      light_number: data[2],
+      zone_number: u16::from_be_bytes([data[3], data[4]]),
+    })
+  }
+}
+
+impl From<LightAttach> for Vec<u8> {
+  fn from(value: LightAttach) -> Self {
+    let [zone_high, zone_low] = value.zone_number.to_be_bytes();
+
+    vec![
+      value.partition_number,
+      value.area_number,
+      value.light_number,
+      zone_high,
+      zone_low,
+    ]
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct TimeDate {
@@ -331,15 +758,25 @@ pub struct TimeDate {
   pub year: u8,
 }
 
-impl From<Vec<u8>> for TimeDate {
-  fn from(data: Vec<u8>) -> Self {
-    TimeDate {
+impl TryFrom<Vec<u8>> for TimeDate {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 5)?;
+
+    Ok(TimeDate {
       hour: data[0],
       minute: data[1],
       month: data[2],
       day: data[3],
       year: data[4],
-    }
+    })
+  }
+}
+
+impl From<TimeDate> for Vec<u8> {
+  fn from(value: TimeDate) -> Self {
+    vec![value.hour, value.minute, value.month, value.day, value.year]
   }
 }
 
@@ -360,6 +797,15 @@ impl From<u8> for SuperBusDeviceStatus {
   }
 }
 
+impl From<SuperBusDeviceStatus> for u8 {
+  fn from(value: SuperBusDeviceStatus) -> Self {
+    match value {
+      SuperBusDeviceStatus::Ok => 0x0,
+      SuperBusDeviceStatus::Failed => 0x1,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct SuperBusDeviceData {
@@ -369,14 +815,33 @@ pub struct SuperBusDeviceData {
   pub device_status: SuperBusDeviceStatus,
 }
 
-impl From<Vec<u8>> for SuperBusDeviceData {
-  fn from(data: Vec<u8>) -> Self {
-    SuperBusDeviceData {
+impl TryFrom<Vec<u8>> for SuperBusDeviceData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 6)?;
+
+    Ok(SuperBusDeviceData {
       partition_number: data[0],
       area_number: data[1],
       device_id: (data[2], data[3], data[4]),
       device_status: SuperBusDeviceStatus::from(data[5]),
-    }
+    })
+  }
+}
+
+impl From<SuperBusDeviceData> for Vec<u8> {
+  fn from(value: SuperBusDeviceData) -> Self {
+    let (id_high, id_mid, id_low) = value.device_id;
+
+    vec![
+      value.partition_number,
+      value.area_number,
+      id_high,
+      id_mid,
+      id_low,
+      value.device_status.into(),
+    ]
   }
 }
 
@@ -414,9 +879,13 @@ pub enum SuperBusDeviceCapabilityData {
   LedDisplay,
 }
 
-impl From<&[u8]> for SuperBusDeviceCapabilityData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for SuperBusDeviceCapabilityData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 2)?;
+
+    Ok(match data[0] {
       0x00 => SuperBusDeviceCapabilityData::PowerSupervision,
       0x01 => SuperBusDeviceCapabilityData::AccessControl,
       0x02 => SuperBusDeviceCapabilityData::AnalogSmoke,
@@ -443,6 +912,38 @@ impl From<&[u8]> for SuperBusDeviceCapabilityData {
       0x17 => SuperBusDeviceCapabilityData::Cryptography,
       0x18 => SuperBusDeviceCapabilityData::LedDisplay,
       _ => SuperBusDeviceCapabilityData::Unknown,
+    })
+  }
+}
+
+impl From<SuperBusDeviceCapabilityData> for Vec<u8> {
+  fn from(value: SuperBusDeviceCapabilityData) -> Self {
+    match value {
+      SuperBusDeviceCapabilityData::PowerSupervision => vec![0x00],
+      SuperBusDeviceCapabilityData::AccessControl => vec![0x01],
+      SuperBusDeviceCapabilityData::AnalogSmoke => vec![0x02],
+      SuperBusDeviceCapabilityData::AudioListenIn => vec![0x03],
+      SuperBusDeviceCapabilityData::SnapCardSupervision => vec![0x04],
+      SuperBusDeviceCapabilityData::Microburst => vec![0x05],
+      SuperBusDeviceCapabilityData::DualPhoneLine => vec![0x06],
+      SuperBusDeviceCapabilityData::EnergyManagement => vec![0x07],
+      SuperBusDeviceCapabilityData::InputZones(count) => vec![0x08, count],
+      SuperBusDeviceCapabilityData::PhastAutomationSystemManager => vec![0x09],
+      SuperBusDeviceCapabilityData::PhoneInterface => vec![0x0A],
+      SuperBusDeviceCapabilityData::RelayOutputs(count) => vec![0x0B, count],
+      SuperBusDeviceCapabilityData::RFReceiver => vec![0x0C],
+      SuperBusDeviceCapabilityData::RFTransmitter => vec![0x0D],
+      SuperBusDeviceCapabilityData::ParallelPrinter => vec![0x0E],
+      SuperBusDeviceCapabilityData::Unknown => vec![0x0F],
+      SuperBusDeviceCapabilityData::LedTouchpad => vec![0x10],
+      SuperBusDeviceCapabilityData::OneLineTwoLineBltTouchpad => vec![0x11],
+      SuperBusDeviceCapabilityData::GuiTouchpad => vec![0x12],
+      SuperBusDeviceCapabilityData::VoiceEvacuation => vec![0x13],
+      SuperBusDeviceCapabilityData::Pager => vec![0x14],
+      SuperBusDeviceCapabilityData::DownloadableCodeData => vec![0x15],
+      SuperBusDeviceCapabilityData::JTechPremisePager => vec![0x16],
+      SuperBusDeviceCapabilityData::Cryptography => vec![0x17],
+      SuperBusDeviceCapabilityData::LedDisplay => vec![0x18],
     }
   }
 }
@@ -454,16 +955,29 @@ pub struct SuperBusDeviceCapability {
   pub capability: SuperBusDeviceCapabilityData,
 }
 
-impl From<Vec<u8>> for SuperBusDeviceCapability {
-  fn from(data: Vec<u8>) -> Self {
-    SuperBusDeviceCapability {
+impl TryFrom<Vec<u8>> for SuperBusDeviceCapability {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 3)?;
+
+    Ok(SuperBusDeviceCapability {
       device_id: (data[0], data[1], data[2]),
-      capability: SuperBusDeviceCapabilityData::from(&data[3..]),
-    }
+      capability: SuperBusDeviceCapabilityData::try_from(&data[3..])?,
+    })
   }
 }
 
-#[derive(Debug, Clone)]
+impl From<SuperBusDeviceCapability> for Vec<u8> {
+  fn from(value: SuperBusDeviceCapability) -> Self {
+    let (id_high, id_mid, id_low) = value.device_id;
+
+    [vec![id_high, id_mid, id_low], value.capability.into()].concat()
+  }
+}
+
+/// Who a user number belongs to: a code, or something that arms and disarms without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub enum CodeType {
   User(u8),
@@ -476,14 +990,26 @@ pub enum CodeType {
   QuickArm,
   KeySwitch,
   System,
+  /// a user number outside the panel's code table, like a keyfob or other device acting without a code
+  Other(u16),
 }
 
 impl From<u8> for CodeType {
   fn from(data: u8) -> Self {
-    match data {
-      0_u8..=229_u8 => CodeType::User(data),
-      230_u8..=237_u8 => CodeType::Master(data - 230),
-      238_u8..=245_u8 => CodeType::Duress(data - 238),
+    CodeType::from(u16::from(data))
+  }
+}
+
+impl From<u16> for CodeType {
+  fn from(data: u16) -> Self {
+    let Ok(number) = u8::try_from(data) else {
+      return CodeType::Other(data);
+    };
+
+    match number {
+      0_u8..=229_u8 => CodeType::User(number),
+      230_u8..=237_u8 => CodeType::Master(number - 230),
+      238_u8..=245_u8 => CodeType::Duress(number - 238),
       246 => CodeType::SystemMaster,
       247 => CodeType::Installer,
       248 => CodeType::Dealer,
@@ -491,7 +1017,25 @@ impl From<u8> for CodeType {
       250 => CodeType::QuickArm,
       251 => CodeType::KeySwitch,
       252 => CodeType::System,
-      _ => CodeType::User(data),
+      _ => CodeType::Other(data),
+    }
+  }
+}
+
+impl From<CodeType> for u16 {
+  fn from(value: CodeType) -> Self {
+    match value {
+      CodeType::User(number) => number.into(),
+      CodeType::Master(number) => u16::from(number) + 230,
+      CodeType::Duress(number) => u16::from(number) + 238,
+      CodeType::SystemMaster => 246,
+      CodeType::Installer => 247,
+      CodeType::Dealer => 248,
+      CodeType::Avm => 249,
+      CodeType::QuickArm => 250,
+      CodeType::KeySwitch => 251,
+      CodeType::System => 252,
+      CodeType::Other(number) => number,
     }
   }
 }
@@ -504,21 +1048,38 @@ pub struct UserData {
   pub code: Option<(u8, u8, u8, u8)>,
 }
 
-impl From<Vec<u8>> for UserData {
-  fn from(data: Vec<u8>) -> Self {
-    if data.len() > 2 {
+impl TryFrom<Vec<u8>> for UserData {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 2)?;
+
+    // the code is only sent when it's available to the automation module
+    Ok(if data.len() > 4 {
       UserData {
         number: (data[0], data[1]),
-        user_type: CodeType::from(data[1]),
+        user_type: CodeType::from(u16::from_be_bytes([data[0], data[1]])),
         // the code is stored in BCD format
         code: Some(((data[3] >> 4), data[3] & 0x0F, (data[4] >> 4), data[4] & 0x0F)),
       }
     } else {
       UserData {
         number: (data[0], data[1]),
-        user_type: CodeType::from(data[1]),
+        user_type: CodeType::from(u16::from_be_bytes([data[0], data[1]])),
         code: None,
       }
+    })
+  }
+}
+
+impl From<UserData> for Vec<u8> {
+  fn from(value: UserData) -> Self {
+    let (number_high, number_low) = value.number;
+
+    match value.code {
+      // the code is stored in BCD format
+      Some((a, b, c, d)) => vec![number_high, number_low, 0x0, (a << 4) | b, (c << 4) | d],
+      None => vec![number_high, number_low],
     }
   }
 }
@@ -531,6 +1092,8 @@ pub enum EventSource {
   Zone,
   System,
   RemotePhone,
+  /// a source type this crate doesn't know about, kept so it can be encoded again
+  Unknown(u8),
 }
 
 impl From<u8> for EventSource {
@@ -541,7 +1104,20 @@ impl From<u8> for EventSource {
       0x2 => EventSource::Zone,
       0x3 => EventSource::System,
       0x4 => EventSource::RemotePhone,
-      _ => EventSource::BusDevice,
+      _ => EventSource::Unknown(data),
+    }
+  }
+}
+
+impl From<EventSource> for u8 {
+  fn from(value: EventSource) -> Self {
+    match value {
+      EventSource::BusDevice => 0x0,
+      EventSource::LocalPhone => 0x1,
+      EventSource::Zone => 0x2,
+      EventSource::System => 0x3,
+      EventSource::RemotePhone => 0x4,
+      EventSource::Unknown(data) => data,
     }
   }
 }
@@ -593,11 +1169,16 @@ pub enum AlarmEventData {
   Interior,
   Near,
   WaterAlarm,
+  Unknown(u8),
 }
 
-impl From<&[u8]> for AlarmEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for AlarmEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => AlarmEventData::Unspecified,
       0x01 => AlarmEventData::Fire,
       0x02 => AlarmEventData::FirePanic,
@@ -638,11 +1219,60 @@ impl From<&[u8]> for AlarmEventData {
       0x25 => AlarmEventData::Interior,
       0x26 => AlarmEventData::Near,
       0x27 => AlarmEventData::WaterAlarm,
-      _ => AlarmEventData::Unspecified,
+      other => AlarmEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<AlarmEventData> for Vec<u8> {
+  fn from(value: AlarmEventData) -> Self {
+    match value {
+      AlarmEventData::Unspecified => vec![0x00, 0x00, 0x00],
+      AlarmEventData::Fire => vec![0x01, 0x00, 0x00],
+      AlarmEventData::FirePanic => vec![0x02, 0x00, 0x00],
+      AlarmEventData::Police => vec![0x03, 0x00, 0x00],
+      AlarmEventData::PolicePanic => vec![0x04, 0x00, 0x00],
+      AlarmEventData::Medical => vec![0x05, 0x00, 0x00],
+      AlarmEventData::MedicalPanic => vec![0x06, 0x00, 0x00],
+      AlarmEventData::Auxiliary => vec![0x07, 0x00, 0x00],
+      AlarmEventData::AuxiliaryPanic => vec![0x08, 0x00, 0x00],
+      AlarmEventData::Tamper => vec![0x09, 0x00, 0x00],
+      AlarmEventData::NoActivity => vec![0x0A, 0x00, 0x00],
+      AlarmEventData::Suspicion => vec![0x0B, 0x00, 0x00],
+      AlarmEventData::NotUsed => vec![0x0C, 0x00, 0x00],
+      AlarmEventData::LowTemperature => vec![0x0D, 0x00, 0x00],
+      AlarmEventData::HighTemperature => vec![0x0E, 0x00, 0x00],
+      AlarmEventData::KeystrokeViolation => vec![0x0F, 0x00, 0x00],
+      AlarmEventData::Duress => vec![0x10, 0x00, 0x00],
+      AlarmEventData::ExitFault => vec![0x11, 0x00, 0x00],
+      AlarmEventData::ExplosiveGas => vec![0x12, 0x00, 0x00],
+      AlarmEventData::CarbonMonoxide => vec![0x13, 0x00, 0x00],
+      AlarmEventData::Environmental => vec![0x14, 0x00, 0x00],
+      AlarmEventData::Latchkey(high, low) => vec![0x15, high, low],
+      AlarmEventData::EquipmentTamper => vec![0x16, 0x00, 0x00],
+      AlarmEventData::Holdup => vec![0x17, 0x00, 0x00],
+      AlarmEventData::Sprinkler => vec![0x18, 0x00, 0x00],
+      AlarmEventData::Heat => vec![0x19, 0x00, 0x00],
+      AlarmEventData::SirenTamper => vec![0x1A, 0x00, 0x00],
+      AlarmEventData::Smoke => vec![0x1B, 0x00, 0x00],
+      AlarmEventData::RepeaterTamper => vec![0x1C, 0x00, 0x00],
+      AlarmEventData::FirePumpActive => vec![0x1D, 0x00, 0x00],
+      AlarmEventData::FirePumpFailure => vec![0x1E, 0x00, 0x00],
+      AlarmEventData::FireGateValve => vec![0x1F, 0x00, 0x00],
+      AlarmEventData::LowCO2Pressure => vec![0x20, 0x00, 0x00],
+      AlarmEventData::LowLiquidPressure => vec![0x21, 0x00, 0x00],
+      AlarmEventData::LowLiquidLevel => vec![0x22, 0x00, 0x00],
+      AlarmEventData::EntryExit => vec![0x23, 0x00, 0x00],
+      AlarmEventData::Perimeter => vec![0x24, 0x00, 0x00],
+      AlarmEventData::Interior => vec![0x25, 0x00, 0x00],
+      AlarmEventData::Near => vec![0x26, 0x00, 0x00],
+      AlarmEventData::WaterAlarm => vec![0x27, 0x00, 0x00],
+      AlarmEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
 
+/// The specific type of a fire or nonfire trouble, which share their numbering.
 #[derive(Debug, Clone)]
 #[cfg_attr(
   feature = "json",
@@ -670,11 +1300,16 @@ pub enum FireEventData {
   CO2PressureTrouble,
   LiquidPressureTrouble,
   LiquidLevelTrouble,
+  Unknown(u8),
 }
 
-impl From<&[u8]> for FireEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for FireEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 1)?;
+
+    Ok(match data[0] {
       0x00 => FireEventData::Unspecified,
       0x01 => FireEventData::Hardwire,
       0x02 => FireEventData::GroundFault,
@@ -695,7 +1330,35 @@ impl From<&[u8]> for FireEventData {
       0x11 => FireEventData::CO2PressureTrouble,
       0x12 => FireEventData::LiquidPressureTrouble,
       0x13 => FireEventData::LiquidLevelTrouble,
-      _ => FireEventData::Unspecified,
+      other => FireEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<FireEventData> for Vec<u8> {
+  fn from(value: FireEventData) -> Self {
+    match value {
+      FireEventData::Unspecified => vec![0x00, 0x00, 0x00],
+      FireEventData::Hardwire => vec![0x01, 0x00, 0x00],
+      FireEventData::GroundFault => vec![0x02, 0x00, 0x00],
+      FireEventData::Device => vec![0x03, 0x00, 0x00],
+      FireEventData::Supervisory => vec![0x04, 0x00, 0x00],
+      FireEventData::LowBattery => vec![0x05, 0x00, 0x00],
+      FireEventData::Tamper => vec![0x06, 0x00, 0x00],
+      FireEventData::Sam => vec![0x07, 0x00, 0x00],
+      FireEventData::PartialObscurity => vec![0x08, 0x00, 0x00],
+      FireEventData::Jam => vec![0x09, 0x00, 0x00],
+      FireEventData::ZoneAcFail => vec![0x0A, 0x00, 0x00],
+      FireEventData::NU => vec![0x0B, 0x00, 0x00],
+      FireEventData::NacTrouble => vec![0x0C, 0x00, 0x00],
+      FireEventData::AnalogZoneTrouble => vec![0x0D, 0x00, 0x00],
+      FireEventData::FireSupervisory => vec![0x0E, 0x00, 0x00],
+      FireEventData::PumpFail => vec![0x0F, 0x00, 0x00],
+      FireEventData::FireGateValveClosed => vec![0x10, 0x00, 0x00],
+      FireEventData::CO2PressureTrouble => vec![0x11, 0x00, 0x00],
+      FireEventData::LiquidPressureTrouble => vec![0x12, 0x00, 0x00],
+      FireEventData::LiquidLevelTrouble => vec![0x13, 0x00, 0x00],
+      FireEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -711,16 +1374,33 @@ pub enum BypassEventData {
   IndirectBypass(u8, u8),
   SwingerBypass,
   Inhibit(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for BypassEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for BypassEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => BypassEventData::DirectBypass(data[1], data[2]),
       0x01 => BypassEventData::IndirectBypass(data[1], data[2]),
       0x02 => BypassEventData::SwingerBypass,
       0x03 => BypassEventData::Inhibit(data[1], data[2]),
-      _ => BypassEventData::DirectBypass(data[1], data[2]),
+      other => BypassEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<BypassEventData> for Vec<u8> {
+  fn from(value: BypassEventData) -> Self {
+    match value {
+      BypassEventData::DirectBypass(high, low) => vec![0x00, high, low],
+      BypassEventData::IndirectBypass(high, low) => vec![0x01, high, low],
+      BypassEventData::SwingerBypass => vec![0x02, 0x00, 0x00],
+      BypassEventData::Inhibit(high, low) => vec![0x03, high, low],
+      BypassEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -741,11 +1421,16 @@ pub enum OpeningEventData {
   OpenUsingKeyfob,
   ScheduledOpen,
   RemoteOpen(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for OpeningEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for OpeningEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => OpeningEventData::NormalOpen(data[1], data[2]),
       0x01 => OpeningEventData::EarlyOpen(data[1], data[2]),
       0x02 => OpeningEventData::LateOpen(data[1], data[2]),
@@ -755,7 +1440,24 @@ impl From<&[u8]> for OpeningEventData {
       0x06 => OpeningEventData::OpenUsingKeyfob,
       0x07 => OpeningEventData::ScheduledOpen,
       0x08 => OpeningEventData::RemoteOpen(data[1], data[2]),
-      _ => OpeningEventData::NormalOpen(data[1], data[2]),
+      other => OpeningEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<OpeningEventData> for Vec<u8> {
+  fn from(value: OpeningEventData) -> Self {
+    match value {
+      OpeningEventData::NormalOpen(high, low) => vec![0x00, high, low],
+      OpeningEventData::EarlyOpen(high, low) => vec![0x01, high, low],
+      OpeningEventData::LateOpen(high, low) => vec![0x02, high, low],
+      OpeningEventData::FailToOpen => vec![0x03, 0x00, 0x00],
+      OpeningEventData::OpenException(high, low) => vec![0x04, high, low],
+      OpeningEventData::OpenExtension(high, low) => vec![0x05, high, low],
+      OpeningEventData::OpenUsingKeyfob => vec![0x06, 0x00, 0x00],
+      OpeningEventData::ScheduledOpen => vec![0x07, 0x00, 0x00],
+      OpeningEventData::RemoteOpen(high, low) => vec![0x08, high, low],
+      OpeningEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -777,11 +1479,16 @@ pub enum ClosingEventData {
   ScheduledClose,
   RemoteClose(u8, u8),
   RecentClose(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for ClosingEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for ClosingEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => ClosingEventData::NormalClose(data[1], data[2]),
       0x01 => ClosingEventData::EarlyClose(data[1], data[2]),
       0x02 => ClosingEventData::LateClose(data[1], data[2]),
@@ -792,7 +1499,25 @@ impl From<&[u8]> for ClosingEventData {
       0x07 => ClosingEventData::ScheduledClose,
       0x08 => ClosingEventData::RemoteClose(data[1], data[2]),
       0x09 => ClosingEventData::RecentClose(data[1], data[2]),
-      _ => ClosingEventData::NormalClose(data[1], data[2]),
+      other => ClosingEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<ClosingEventData> for Vec<u8> {
+  fn from(value: ClosingEventData) -> Self {
+    match value {
+      ClosingEventData::NormalClose(high, low) => vec![0x00, high, low],
+      ClosingEventData::EarlyClose(high, low) => vec![0x01, high, low],
+      ClosingEventData::LateClose(high, low) => vec![0x02, high, low],
+      ClosingEventData::FailToClose => vec![0x03, 0x00, 0x00],
+      ClosingEventData::CloseException(high, low) => vec![0x04, high, low],
+      ClosingEventData::CloseExtension(high, low) => vec![0x05, high, low],
+      ClosingEventData::CloseUsingKeyfob => vec![0x06, 0x00, 0x00],
+      ClosingEventData::ScheduledClose => vec![0x07, 0x00, 0x00],
+      ClosingEventData::RemoteClose(high, low) => vec![0x08, high, low],
+      ClosingEventData::RecentClose(high, low) => vec![0x09, high, low],
+      ClosingEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -814,11 +1539,16 @@ pub enum PartitionConfigEventData {
   ArmingOrOcScheduleChanged,
   ZoneAdded,
   ZoneDeleted,
+  Unknown(u8),
 }
 
-impl From<&[u8]> for PartitionConfigEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for PartitionConfigEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => PartitionConfigEventData::UserAccessCodeAdded(data[1], data[2]),
       0x01 => PartitionConfigEventData::UserAccessCodeDeleted(data[1], data[2]),
       0x02 => PartitionConfigEventData::UserAccessCodeChanged(data[1], data[2]),
@@ -829,7 +1559,25 @@ impl From<&[u8]> for PartitionConfigEventData {
       0x07 => PartitionConfigEventData::ArmingOrOcScheduleChanged,
       0x08 => PartitionConfigEventData::ZoneAdded,
       0x09 => PartitionConfigEventData::ZoneDeleted,
-      _ => PartitionConfigEventData::UserAccessCodeAdded(data[1], data[2]),
+      other => PartitionConfigEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<PartitionConfigEventData> for Vec<u8> {
+  fn from(value: PartitionConfigEventData) -> Self {
+    match value {
+      PartitionConfigEventData::UserAccessCodeAdded(high, low) => vec![0x00, high, low],
+      PartitionConfigEventData::UserAccessCodeDeleted(high, low) => vec![0x01, high, low],
+      PartitionConfigEventData::UserAccessCodeChanged(high, low) => vec![0x02, high, low],
+      PartitionConfigEventData::UserAccessCodeExpired(high, low) => vec![0x03, high, low],
+      PartitionConfigEventData::UserCodeAuthorityChanged => vec![0x04, 0x00, 0x00],
+      PartitionConfigEventData::AuthorityLevelsChanged => vec![0x05, 0x00, 0x00],
+      PartitionConfigEventData::ScheduleChanged => vec![0x06, 0x00, 0x00],
+      PartitionConfigEventData::ArmingOrOcScheduleChanged => vec![0x07, 0x00, 0x00],
+      PartitionConfigEventData::ZoneAdded => vec![0x08, 0x00, 0x00],
+      PartitionConfigEventData::ZoneDeleted => vec![0x09, 0x00, 0x00],
+      PartitionConfigEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -858,11 +1606,16 @@ pub enum PartitionEventData {
   AutoForceArmFailed,
   ArmingProtestBegun(u8, u8),
   ArmingProtestEnded(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for PartitionEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for PartitionEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => PartitionEventData::ScheduleOn(data[1], data[2]),
       0x01 => PartitionEventData::ScheduleOff(data[1], data[2]),
       0x02 => PartitionEventData::LatchkeyOn,
@@ -880,7 +1633,32 @@ impl From<&[u8]> for PartitionEventData {
       0x0E => PartitionEventData::AutoForceArmFailed,
       0x0F => PartitionEventData::ArmingProtestBegun(data[1], data[2]),
       0x10 => PartitionEventData::ArmingProtestEnded(data[1], data[2]),
-      _ => PartitionEventData::ScheduleOn(data[1], data[2]),
+      other => PartitionEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<PartitionEventData> for Vec<u8> {
+  fn from(value: PartitionEventData) -> Self {
+    match value {
+      PartitionEventData::ScheduleOn(high, low) => vec![0x00, high, low],
+      PartitionEventData::ScheduleOff(high, low) => vec![0x01, high, low],
+      PartitionEventData::LatchkeyOn => vec![0x02, 0x00, 0x00],
+      PartitionEventData::LatchkeyOff => vec![0x03, 0x00, 0x00],
+      PartitionEventData::SmokeDetectorsReset => vec![0x04, 0x00, 0x00],
+      PartitionEventData::ValidUserAccessCodeEntered(high, low) => vec![0x05, high, low],
+      PartitionEventData::ArmingLevelChanged(high, low) => vec![0x06, high, low],
+      PartitionEventData::AlarmReported => vec![0x07, 0x00, 0x00],
+      PartitionEventData::AgentRelease => vec![0x08, 0x00, 0x00],
+      PartitionEventData::AgentReleaseRestoral => vec![0x09, 0x00, 0x00],
+      PartitionEventData::PartitionRemoteAccess => vec![0x0A, 0x00, 0x00],
+      PartitionEventData::KeystrokeViolationInPartition => vec![0x0B, 0x00, 0x00],
+      PartitionEventData::ManualForceArm(high, low) => vec![0x0C, high, low],
+      PartitionEventData::AutoForceArm => vec![0x0D, 0x00, 0x00],
+      PartitionEventData::AutoForceArmFailed => vec![0x0E, 0x00, 0x00],
+      PartitionEventData::ArmingProtestBegun(high, low) => vec![0x0F, high, low],
+      PartitionEventData::ArmingProtestEnded(high, low) => vec![0x10, high, low],
+      PartitionEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -908,11 +1686,16 @@ pub enum PartitionTestEventData {
   InstallerSensorTestIncomplete,
   InstallerSensorTestTrip,
   FireDrillStarted(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for PartitionTestEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for PartitionTestEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => PartitionTestEventData::ManualPhoneTest(data[1], data[2]),
       0x01 => PartitionTestEventData::AutoPhoneTest,
       0x02 => PartitionTestEventData::AutoPhoneTestWithExistingTrouble,
@@ -929,7 +1712,31 @@ impl From<&[u8]> for PartitionTestEventData {
       0x0D => PartitionTestEventData::InstallerSensorTestIncomplete,
       0x0E => PartitionTestEventData::InstallerSensorTestTrip,
       0x0F => PartitionTestEventData::FireDrillStarted(data[1], data[2]),
-      _ => PartitionTestEventData::ManualPhoneTest(data[1], data[2]),
+      other => PartitionTestEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<PartitionTestEventData> for Vec<u8> {
+  fn from(value: PartitionTestEventData) -> Self {
+    match value {
+      PartitionTestEventData::ManualPhoneTest(high, low) => vec![0x00, high, low],
+      PartitionTestEventData::AutoPhoneTest => vec![0x01, 0x00, 0x00],
+      PartitionTestEventData::AutoPhoneTestWithExistingTrouble => vec![0x02, 0x00, 0x00],
+      PartitionTestEventData::PhoneTestOk => vec![0x03, 0x00, 0x00],
+      PartitionTestEventData::PhoneTestFailed => vec![0x04, 0x00, 0x00],
+      PartitionTestEventData::UserSensorTestStarted(high, low) => vec![0x05, high, low],
+      PartitionTestEventData::UserSensorTestEnded(high, low) => vec![0x06, high, low],
+      PartitionTestEventData::UserSenorTestCompleted(high, low) => vec![0x07, high, low],
+      PartitionTestEventData::UserSensorTestIncomplete(high, low) => vec![0x08, high, low],
+      PartitionTestEventData::UserSensorTestTrip => vec![0x09, 0x00, 0x00],
+      PartitionTestEventData::InstallerSensorTestStarted => vec![0x0A, 0x00, 0x00],
+      PartitionTestEventData::InstallerSensorTestEnded => vec![0x0B, 0x00, 0x00],
+      PartitionTestEventData::InstallerSensorTestCompleted => vec![0x0C, 0x00, 0x00],
+      PartitionTestEventData::InstallerSensorTestIncomplete => vec![0x0D, 0x00, 0x00],
+      PartitionTestEventData::InstallerSensorTestTrip => vec![0x0E, 0x00, 0x00],
+      PartitionTestEventData::FireDrillStarted(high, low) => vec![0x0F, high, low],
+      PartitionTestEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -993,11 +1800,16 @@ pub enum SystemTroubleEventData {
   AutomationSupervisoryTrouble,
   MicroburstModuleInitializing,
   PrinterPaperOutTrouble,
+  Unknown(u8),
 }
 
-impl From<&[u8]> for SystemTroubleEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for SystemTroubleEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 1)?;
+
+    Ok(match data[0] {
       0x00 => SystemTroubleEventData::BusReceiverFailure,
       0x01 => SystemTroubleEventData::BusAntennaTamper,
       0x02 => SystemTroubleEventData::MainLowBattery,
@@ -1050,7 +1862,67 @@ impl From<&[u8]> for SystemTroubleEventData {
       0x31 => SystemTroubleEventData::AutomationSupervisoryTrouble,
       0x32 => SystemTroubleEventData::MicroburstModuleInitializing,
       0x33 => SystemTroubleEventData::PrinterPaperOutTrouble,
-      _ => SystemTroubleEventData::BusReceiverFailure,
+      other => SystemTroubleEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<SystemTroubleEventData> for Vec<u8> {
+  fn from(value: SystemTroubleEventData) -> Self {
+    match value {
+      SystemTroubleEventData::BusReceiverFailure => vec![0x00, 0x00, 0x00],
+      SystemTroubleEventData::BusAntennaTamper => vec![0x01, 0x00, 0x00],
+      SystemTroubleEventData::MainLowBattery => vec![0x02, 0x00, 0x00],
+      SystemTroubleEventData::SnapCardLowBattery => vec![0x03, 0x00, 0x00],
+      SystemTroubleEventData::ModuleLowBattery => vec![0x04, 0x00, 0x00],
+      SystemTroubleEventData::MainAcFailure => vec![0x05, 0x00, 0x00],
+      SystemTroubleEventData::SnapCardAcFailure => vec![0x06, 0x00, 0x00],
+      SystemTroubleEventData::ModuleAcFailure => vec![0x07, 0x00, 0x00],
+      SystemTroubleEventData::AuxPowerFailure => vec![0x08, 0x00, 0x00],
+      SystemTroubleEventData::BusShutdown => vec![0x09, 0x00, 0x00],
+      SystemTroubleEventData::BusLowPowerMode => vec![0x0A, 0x00, 0x00],
+      SystemTroubleEventData::PhoneLine1Failure => vec![0x0B, 0x00, 0x00],
+      SystemTroubleEventData::PhoneLine2Failure => vec![0x0C, 0x00, 0x00],
+      SystemTroubleEventData::RemotePhoneTamper => vec![0x0D, 0x00, 0x00],
+      SystemTroubleEventData::WatchdogReset => vec![0x0E, 0x00, 0x00],
+      SystemTroubleEventData::RamFailure => vec![0x0F, 0x00, 0x00],
+      SystemTroubleEventData::FlashFailure => vec![0x10, 0x00, 0x00],
+      SystemTroubleEventData::PrinterError => vec![0x11, 0x00, 0x00],
+      SystemTroubleEventData::HistoryBufferAlmostFull => vec![0x12, 0x00, 0x00],
+      SystemTroubleEventData::HistoryBufferOverflow => vec![0x13, 0x00, 0x00],
+      SystemTroubleEventData::ReportBufferOverflow => vec![0x14, 0x00, 0x00],
+      SystemTroubleEventData::BusDeviceFailure => vec![0x15, 0x00, 0x00],
+      SystemTroubleEventData::FailureToCommunicate => vec![0x16, 0x00, 0x00],
+      SystemTroubleEventData::LongRangeRadioTrouble => vec![0x17, 0x00, 0x00],
+      SystemTroubleEventData::ModuleTamperTrouble => vec![0x18, 0x00, 0x00],
+      SystemTroubleEventData::UnenrolledModuleTrouble => vec![0x19, 0x00, 0x00],
+      SystemTroubleEventData::AudioOutputTrouble => vec![0x1A, 0x00, 0x00],
+      SystemTroubleEventData::AnalogModuleTrouble => vec![0x1B, 0x00, 0x00],
+      SystemTroubleEventData::CellModuleTrouble => vec![0x1C, 0x00, 0x00],
+      SystemTroubleEventData::Buddy1Failure => vec![0x1D, 0x00, 0x00],
+      SystemTroubleEventData::Buddy2Failure => vec![0x1E, 0x00, 0x00],
+      SystemTroubleEventData::Buddy3Failure => vec![0x1F, 0x00, 0x00],
+      SystemTroubleEventData::Buddy4Failure => vec![0x20, 0x00, 0x00],
+      SystemTroubleEventData::SnapCardTrouble => vec![0x21, 0x00, 0x00],
+      SystemTroubleEventData::AnalogLoopShort => vec![0x22, 0x00, 0x00],
+      SystemTroubleEventData::AnalogLoopBreak => vec![0x23, 0x00, 0x00],
+      SystemTroubleEventData::AnalogAddress0 => vec![0x24, 0x00, 0x00],
+      SystemTroubleEventData::UnenrolledAnalogHead => vec![0x25, 0x00, 0x00],
+      SystemTroubleEventData::DuplicateAnalogHead => vec![0x26, 0x00, 0x00],
+      SystemTroubleEventData::AnalogModuleInitializing => vec![0x27, 0x00, 0x00],
+      SystemTroubleEventData::MicrophoneSwitchTrouble => vec![0x28, 0x00, 0x00],
+      SystemTroubleEventData::MicrophoneTrouble => vec![0x29, 0x00, 0x00],
+      SystemTroubleEventData::MicrophoneWiringTrouble => vec![0x2A, 0x00, 0x00],
+      SystemTroubleEventData::JTechPremisePagingTrouble => vec![0x2B, 0x00, 0x00],
+      SystemTroubleEventData::VoiceSirenTamperTrouble => vec![0x2C, 0x00, 0x00],
+      SystemTroubleEventData::MicroburstTransmitFailure => vec![0x2D, 0x00, 0x00],
+      SystemTroubleEventData::MicroburstTransmitDisabled => vec![0x2E, 0x00, 0x00],
+      SystemTroubleEventData::MicroburstModuleFailure => vec![0x2F, 0x00, 0x00],
+      SystemTroubleEventData::MicroburstNotInService => vec![0x30, 0x00, 0x00],
+      SystemTroubleEventData::AutomationSupervisoryTrouble => vec![0x31, 0x00, 0x00],
+      SystemTroubleEventData::MicroburstModuleInitializing => vec![0x32, 0x00, 0x00],
+      SystemTroubleEventData::PrinterPaperOutTrouble => vec![0x33, 0x00, 0x00],
+      SystemTroubleEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -1078,11 +1950,16 @@ pub enum SystemConfigChangeEventData {
   PanelFirstService,
   PanelBackInService,
   InstallerCodeChanged,
+  Unknown(u8),
 }
 
-impl From<&[u8]> for SystemConfigChangeEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for SystemConfigChangeEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 1)?;
+
+    Ok(match data[0] {
       0x00 => SystemConfigChangeEventData::ProgramModeEntry,
       0x01 => SystemConfigChangeEventData::ProgramModeExitWithoutChange,
       0x02 => SystemConfigChangeEventData::ProgramModeExitWithChange,
@@ -1099,7 +1976,31 @@ impl From<&[u8]> for SystemConfigChangeEventData {
       0x0D => SystemConfigChangeEventData::PanelFirstService,
       0x0E => SystemConfigChangeEventData::PanelBackInService,
       0x0F => SystemConfigChangeEventData::InstallerCodeChanged,
-      _ => SystemConfigChangeEventData::ProgramModeEntry,
+      other => SystemConfigChangeEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<SystemConfigChangeEventData> for Vec<u8> {
+  fn from(value: SystemConfigChangeEventData) -> Self {
+    match value {
+      SystemConfigChangeEventData::ProgramModeEntry => vec![0x00, 0x00, 0x00],
+      SystemConfigChangeEventData::ProgramModeExitWithoutChange => vec![0x01, 0x00, 0x00],
+      SystemConfigChangeEventData::ProgramModeExitWithChange => vec![0x02, 0x00, 0x00],
+      SystemConfigChangeEventData::DownloaderSessionStart => vec![0x03, 0x00, 0x00],
+      SystemConfigChangeEventData::DownloaderSessionEndWithoutChange => vec![0x04, 0x00, 0x00],
+      SystemConfigChangeEventData::DownloaderSessionEndWithChange => vec![0x05, 0x00, 0x00],
+      SystemConfigChangeEventData::DownloaderError => vec![0x06, 0x00, 0x00],
+      SystemConfigChangeEventData::DownloaderConnectionDenied => vec![0x07, 0x00, 0x00],
+      SystemConfigChangeEventData::DateTimeChanged => vec![0x08, 0x00, 0x00],
+      SystemConfigChangeEventData::ModuleAdded => vec![0x09, 0x00, 0x00],
+      SystemConfigChangeEventData::ModuleDeleted => vec![0x0A, 0x00, 0x00],
+      SystemConfigChangeEventData::SpeechTokensChanged => vec![0x0B, 0x00, 0x00],
+      SystemConfigChangeEventData::CodeChanged => vec![0x0C, 0x00, 0x00],
+      SystemConfigChangeEventData::PanelFirstService => vec![0x0D, 0x00, 0x00],
+      SystemConfigChangeEventData::PanelBackInService => vec![0x0E, 0x00, 0x00],
+      SystemConfigChangeEventData::InstallerCodeChanged => vec![0x0F, 0x00, 0x00],
+      SystemConfigChangeEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -1118,11 +2019,16 @@ pub enum SystemEventData {
   HistoryBufferCleared,
   OutputOn(u8, u8),
   OutputOff(u8, u8),
+  Unknown(u8),
 }
 
-impl From<&[u8]> for SystemEventData {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
+impl TryFrom<&[u8]> for SystemEventData {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 3)?;
+
+    Ok(match data[0] {
       0x00 => SystemEventData::CallbackRequested,
       0x01 => SystemEventData::OutputActivity,
       0x02 => SystemEventData::BuddyReception,
@@ -1130,7 +2036,22 @@ impl From<&[u8]> for SystemEventData {
       0x04 => SystemEventData::HistoryBufferCleared,
       0x05 => SystemEventData::OutputOn(data[1], data[2]),
       0x06 => SystemEventData::OutputOff(data[1], data[2]),
-      _ => SystemEventData::CallbackRequested,
+      other => SystemEventData::Unknown(other),
+    })
+  }
+}
+
+impl From<SystemEventData> for Vec<u8> {
+  fn from(value: SystemEventData) -> Self {
+    match value {
+      SystemEventData::CallbackRequested => vec![0x00, 0x00, 0x00],
+      SystemEventData::OutputActivity => vec![0x01, 0x00, 0x00],
+      SystemEventData::BuddyReception => vec![0x02, 0x00, 0x00],
+      SystemEventData::BuddyTransmissionRequest => vec![0x03, 0x00, 0x00],
+      SystemEventData::HistoryBufferCleared => vec![0x04, 0x00, 0x00],
+      SystemEventData::OutputOn(high, low) => vec![0x05, high, low],
+      SystemEventData::OutputOff(high, low) => vec![0x06, high, low],
+      SystemEventData::Unknown(specific) => vec![specific, 0x00, 0x00],
     }
   }
 }
@@ -1143,33 +2064,218 @@ impl From<&[u8]> for SystemEventData {
 )]
 pub enum Event {
   Alarm(AlarmEventData),
-  Fire(FireEventData),
+  AlarmCancel(AlarmEventData),
+  AlarmRestoral(AlarmEventData),
+  FireTrouble(FireEventData),
+  FireTroubleRestoral(FireEventData),
+  NonfireTrouble(FireEventData),
+  NonfireTroubleRestoral(FireEventData),
   Bypass(BypassEventData),
+  Unbypass(BypassEventData),
   Opening(OpeningEventData),
   Closing(ClosingEventData),
   PartitionConfig(PartitionConfigEventData),
   Partition(PartitionEventData),
   PartitionTest(PartitionTestEventData),
   SystemTrouble(SystemTroubleEventData),
+  SystemTroubleRestoral(SystemTroubleEventData),
   SystemConfigChange(SystemConfigChangeEventData),
   System(SystemEventData),
+  /// a general type this crate doesn't know about
+  Unknown(u8),
+}
+
+impl TryFrom<&[u8]> for Event {
+  type Error = DecodeError;
+
+  fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+    DecodeError::check_len(data, 1)?;
+
+    let specific = &data[1..];
+
+    Ok(match data[0] {
+      0x01 => Event::Alarm(AlarmEventData::try_from(specific)?),
+      0x02 => Event::AlarmCancel(AlarmEventData::try_from(specific)?),
+      0x03 => Event::AlarmRestoral(AlarmEventData::try_from(specific)?),
+      0x04 => Event::FireTrouble(FireEventData::try_from(specific)?),
+      0x05 => Event::FireTroubleRestoral(FireEventData::try_from(specific)?),
+      0x06 => Event::NonfireTrouble(FireEventData::try_from(specific)?),
+      0x07 => Event::NonfireTroubleRestoral(FireEventData::try_from(specific)?),
+      0x08 => Event::Bypass(BypassEventData::try_from(specific)?),
+      0x09 => Event::Unbypass(BypassEventData::try_from(specific)?),
+      0x0A => Event::Opening(OpeningEventData::try_from(specific)?),
+      0x0B => Event::Closing(ClosingEventData::try_from(specific)?),
+      0x0C => Event::PartitionConfig(PartitionConfigEventData::try_from(specific)?),
+      0x0D => Event::Partition(PartitionEventData::try_from(specific)?),
+      0x0E => Event::PartitionTest(PartitionTestEventData::try_from(specific)?),
+      0x0F => Event::SystemTrouble(SystemTroubleEventData::try_from(specific)?),
+      0x10 => Event::SystemTroubleRestoral(SystemTroubleEventData::try_from(specific)?),
+      0x11 => Event::SystemConfigChange(SystemConfigChangeEventData::try_from(specific)?),
+      0x12 => Event::System(SystemEventData::try_from(specific)?),
+      other => Event::Unknown(other),
+    })
+  }
+}
+
+impl From<Event> for Vec<u8> {
+  fn from(value: Event) -> Self {
+    let (general_type, specific): (u8, Vec<u8>) = match value {
+      Event::Alarm(data) => (0x01, data.into()),
+      Event::AlarmCancel(data) => (0x02, data.into()),
+      Event::AlarmRestoral(data) => (0x03, data.into()),
+      Event::FireTrouble(data) => (0x04, data.into()),
+      Event::FireTroubleRestoral(data) => (0x05, data.into()),
+      Event::NonfireTrouble(data) => (0x06, data.into()),
+      Event::NonfireTroubleRestoral(data) => (0x07, data.into()),
+      Event::Bypass(data) => (0x08, data.into()),
+      Event::Unbypass(data) => (0x09, data.into()),
+      Event::Opening(data) => (0x0A, data.into()),
+      Event::Closing(data) => (0x0B, data.into()),
+      Event::PartitionConfig(data) => (0x0C, data.into()),
+      Event::Partition(data) => (0x0D, data.into()),
+      Event::PartitionTest(data) => (0x0E, data.into()),
+      Event::SystemTrouble(data) => (0x0F, data.into()),
+      Event::SystemTroubleRestoral(data) => (0x10, data.into()),
+      Event::SystemConfigChange(data) => (0x11, data.into()),
+      Event::System(data) => (0x12, data.into()),
+      Event::Unknown(general_type) => (general_type, vec![0x00, 0x00, 0x00]),
+    };
+
+    [vec![general_type], specific].concat()
+  }
+}
+
+/// The ongoing conditions the panel reports through [AlarmTrouble] events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum ConditionKind {
+  Alarm,
+  FireTrouble,
+  NonfireTrouble,
+  SystemTrouble,
+}
+
+/// Whether an event starts or ends a [ConditionKind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum ConditionChange {
+  Raised,
+  Cleared,
+}
+
+impl Event {
+  /// the specific type within the event's general type, e.g. which kind of alarm
+  pub fn specific_type(&self) -> u8 {
+    Vec::<u8>::from(self.clone()).get(1).copied().unwrap_or_default()
+  }
+
+  /// the user the event reports, like who opened or bypassed something
+  pub fn user(&self) -> Option<CodeType> {
+    let (high, low) = match self {
+      Event::Alarm(AlarmEventData::Latchkey(high, low))
+      | Event::AlarmCancel(AlarmEventData::Latchkey(high, low))
+      | Event::AlarmRestoral(AlarmEventData::Latchkey(high, low))
+      | Event::Bypass(
+        BypassEventData::DirectBypass(high, low)
+        | BypassEventData::IndirectBypass(high, low)
+        | BypassEventData::Inhibit(high, low),
+      )
+      | Event::Unbypass(
+        BypassEventData::DirectBypass(high, low)
+        | BypassEventData::IndirectBypass(high, low)
+        | BypassEventData::Inhibit(high, low),
+      )
+      | Event::Opening(
+        OpeningEventData::NormalOpen(high, low)
+        | OpeningEventData::EarlyOpen(high, low)
+        | OpeningEventData::LateOpen(high, low)
+        | OpeningEventData::OpenException(high, low)
+        | OpeningEventData::OpenExtension(high, low)
+        | OpeningEventData::RemoteOpen(high, low),
+      )
+      | Event::Closing(
+        ClosingEventData::NormalClose(high, low)
+        | ClosingEventData::EarlyClose(high, low)
+        | ClosingEventData::LateClose(high, low)
+        | ClosingEventData::CloseException(high, low)
+        | ClosingEventData::CloseExtension(high, low)
+        | ClosingEventData::RemoteClose(high, low)
+        | ClosingEventData::RecentClose(high, low),
+      )
+      | Event::PartitionConfig(
+        PartitionConfigEventData::UserAccessCodeAdded(high, low)
+        | PartitionConfigEventData::UserAccessCodeDeleted(high, low)
+        | PartitionConfigEventData::UserAccessCodeChanged(high, low)
+        | PartitionConfigEventData::UserAccessCodeExpired(high, low),
+      )
+      | Event::Partition(
+        PartitionEventData::ValidUserAccessCodeEntered(high, low)
+        | PartitionEventData::ArmingLevelChanged(high, low)
+        | PartitionEventData::ManualForceArm(high, low)
+        | PartitionEventData::ArmingProtestBegun(high, low)
+        | PartitionEventData::ArmingProtestEnded(high, low),
+      )
+      | Event::PartitionTest(
+        PartitionTestEventData::ManualPhoneTest(high, low)
+        | PartitionTestEventData::UserSensorTestStarted(high, low)
+        | PartitionTestEventData::UserSensorTestEnded(high, low)
+        | PartitionTestEventData::UserSenorTestCompleted(high, low)
+        | PartitionTestEventData::UserSensorTestIncomplete(high, low)
+        | PartitionTestEventData::FireDrillStarted(high, low),
+      ) => (high, low),
+      // schedule and output events carry a schedule or output number instead
+      _ => return None,
+    };
+
+    Some(CodeType::from(u16::from_be_bytes([*high, *low])))
+  }
+
+  /// the alarm or trouble this event raises or clears
+  ///
+  /// # returns
+  /// `None` for events that only report something happening, like openings and closings
+  pub fn condition(&self) -> Option<(ConditionKind, ConditionChange)> {
+    Some(match self {
+      Event::Alarm(_) => (ConditionKind::Alarm, ConditionChange::Raised),
+      Event::AlarmCancel(_) | Event::AlarmRestoral(_) => (ConditionKind::Alarm, ConditionChange::Cleared),
+      Event::FireTrouble(_) => (ConditionKind::FireTrouble, ConditionChange::Raised),
+      Event::FireTroubleRestoral(_) => (ConditionKind::FireTrouble, ConditionChange::Cleared),
+      Event::NonfireTrouble(_) => (ConditionKind::NonfireTrouble, ConditionChange::Raised),
+      Event::NonfireTroubleRestoral(_) => (ConditionKind::NonfireTrouble, ConditionChange::Cleared),
+      Event::SystemTrouble(_) => (ConditionKind::SystemTrouble, ConditionChange::Raised),
+      Event::SystemTroubleRestoral(_) => (ConditionKind::SystemTrouble, ConditionChange::Cleared),
+      _ => return None,
+    })
+  }
 }
 
-impl From<&[u8]> for Event {
-  fn from(data: &[u8]) -> Self {
-    match data[0] {
-      0x00 => Event::Alarm(AlarmEventData::from(&data[1..])),
-      0x01 => Event::Fire(FireEventData::from(&data[1..])),
-      0x02 => Event::Bypass(BypassEventData::from(&data[1..])),
-      0x03 => Event::Opening(OpeningEventData::from(&data[1..])),
-      0x04 => Event::Closing(ClosingEventData::from(&data[1..])),
-      0x05 => Event::PartitionConfig(PartitionConfigEventData::from(&data[1..])),
-      0x06 => Event::Partition(PartitionEventData::from(&data[1..])),
-      0x07 => Event::PartitionTest(PartitionTestEventData::from(&data[1..])),
-      0x08 => Event::SystemTrouble(SystemTroubleEventData::from(&data[1..])),
-      0x09 => Event::SystemConfigChange(SystemConfigChangeEventData::from(&data[1..])),
-      0x0A => Event::System(SystemEventData::from(&data[1..])),
-      _ => Event::Alarm(AlarmEventData::from(&data[1..])),
+/// What an [AlarmTrouble] event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase", tag = "type"))]
+pub enum ConditionSource {
+  /// the panel itself or a phone, which the panel reports against the partition
+  Partition {
+    partition_number: u8,
+  },
+  Zone {
+    partition_number: u8,
+    zone_number: u16,
+  },
+  /// a SuperBus device, by its unit id
+  BusDevice {
+    unit_id: u32,
+  },
+}
+
+impl StringIdentifiable for ConditionSource {
+  fn id(&self) -> String {
+    match self {
+      ConditionSource::Partition { partition_number } => format!("p{}", partition_number),
+      ConditionSource::Zone {
+        partition_number,
+        zone_number,
+      } => format!("p{}-z{}", partition_number, zone_number),
+      ConditionSource::BusDevice { unit_id } => format!("d{}", unit_id),
     }
   }
 }
@@ -1184,30 +2290,417 @@ pub struct AlarmTrouble {
   pub event: Event,
 }
 
-impl From<Vec<u8>> for AlarmTrouble {
-  fn from(data: Vec<u8>) -> Self {
-    AlarmTrouble {
+impl TryFrom<Vec<u8>> for AlarmTrouble {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 6)?;
+
+    Ok(AlarmTrouble {
       partition_number: data[0],
       area_number: data[1],
       source_type: EventSource::from(data[2]),
       source_number: (data[3], data[4], data[5]),
-      event: Event::from(&data[6..]),
+      event: Event::try_from(&data[6..])?,
+    })
+  }
+}
+
+impl AlarmTrouble {
+  pub fn source(&self) -> ConditionSource {
+    let (high, mid, low) = self.source_number;
+
+    match self.source_type {
+      EventSource::Zone => ConditionSource::Zone {
+        partition_number: self.partition_number,
+        zone_number: u16::from_be_bytes([mid, low]),
+      },
+      EventSource::BusDevice => ConditionSource::BusDevice {
+        unit_id: u32::from_be_bytes([0, high, mid, low]),
+      },
+      // without knowing what the source number means, the partition is all the event can be pinned on
+      EventSource::LocalPhone | EventSource::System | EventSource::RemotePhone | EventSource::Unknown(_) => {
+        ConditionSource::Partition {
+          partition_number: self.partition_number,
+        }
+      }
+    }
+  }
+}
+
+impl From<AlarmTrouble> for Vec<u8> {
+  fn from(value: AlarmTrouble) -> Self {
+    let (source_high, source_mid, source_low) = value.source_number;
+    let mut data = vec![
+      value.partition_number,
+      value.area_number,
+      value.source_type.into(),
+      source_high,
+      source_mid,
+      source_low,
+    ];
+    data.extend(Vec::<u8>::from(value.event));
+
+    data
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum DelayDirection {
+  Entry,
+  Exit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum DelayExtension {
+  Standard,
+  Extended,
+  TwiceExtended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum DelayPhase {
+  Start,
+  End,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct EntryExitDelay {
+  pub partition_number: u8,
+  pub area_number: u8,
+  pub direction: DelayDirection,
+  pub extension: DelayExtension,
+  pub phase: DelayPhase,
+  /// how long the delay lasts
+  pub seconds: u16,
+}
+
+impl EntryExitDelay {
+  const EXTENSION: u8 = 0x30;
+  const EXIT: u8 = 0x40;
+  const END: u8 = 0x80;
+}
+
+impl TryFrom<Vec<u8>> for EntryExitDelay {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 5)?;
+
+    let flags = data[2];
+    let extension = match (flags & Self::EXTENSION) >> 4 {
+      0x0 => DelayExtension::Standard,
+      0x1 => DelayExtension::Extended,
+      0x2 => DelayExtension::TwiceExtended,
+      value => {
+        return Err(DecodeError::InvalidValue {
+          field: "delay extension",
+          value,
+        })
+      }
+    };
+
+    Ok(EntryExitDelay {
+      partition_number: data[0],
+      area_number: data[1],
+      direction: if flags & Self::EXIT != 0 {
+        DelayDirection::Exit
+      } else {
+        DelayDirection::Entry
+      },
+      extension,
+      phase: if flags & Self::END != 0 {
+        DelayPhase::End
+      } else {
+        DelayPhase::Start
+      },
+      seconds: u16::from_be_bytes([data[3], data[4]]),
+    })
+  }
+}
+
+impl From<EntryExitDelay> for Vec<u8> {
+  fn from(value: EntryExitDelay) -> Self {
+    let extension = match value.extension {
+      DelayExtension::Standard => 0x0,
+      DelayExtension::Extended => 0x1,
+      DelayExtension::TwiceExtended => 0x2,
+    };
+    let mut flags = extension << 4;
+    if value.direction == DelayDirection::Exit {
+      flags |= EntryExitDelay::EXIT;
     }
+    if value.phase == DelayPhase::End {
+      flags |= EntryExitDelay::END;
+    }
+
+    let [seconds_high, seconds_low] = value.seconds.to_be_bytes();
+
+    vec![
+      value.partition_number,
+      value.area_number,
+      flags,
+      seconds_high,
+      seconds_low,
+    ]
+  }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct SirenSetup {
+  pub partition_number: u8,
+  pub area_number: u8,
+  /// how many times to play the cadence, 0 means until stopped
+  pub repetitions: u8,
+  /// the on/off pattern to sound, one bit per eighth of a second starting with the most significant
+  pub cadence: u32,
+}
+
+impl TryFrom<Vec<u8>> for SirenSetup {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 7)?;
+
+    Ok(SirenSetup {
+      partition_number: data[0],
+      area_number: data[1],
+      repetitions: data[2],
+      cadence: u32::from_be_bytes([data[3], data[4], data[5], data[6]]),
+    })
+  }
+}
+
+impl From<SirenSetup> for Vec<u8> {
+  fn from(value: SirenSetup) -> Self {
+    let mut data = vec![value.partition_number, value.area_number, value.repetitions];
+    data.extend(value.cadence.to_be_bytes());
+
+    data
   }
 }
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct SirenStop {
-  partition_number: u8,
-  area_number: u8,
+  pub partition_number: u8,
+  pub area_number: u8,
 }
 
-impl From<Vec<u8>> for SirenStop {
-  fn from(data: Vec<u8>) -> Self {
-    SirenStop {
+impl TryFrom<Vec<u8>> for SirenStop {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 2)?;
+
+    Ok(SirenStop {
       partition_number: data[0],
       area_number: data[1],
+    })
+  }
+}
+
+impl From<SirenStop> for Vec<u8> {
+  fn from(value: SirenStop) -> Self {
+    vec![value.partition_number, value.area_number]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fmt::Debug;
+
+  /// decode each mask in `table` and check it encodes back to the same bits
+  fn check_bits<B, T>(table: &[(B, T)], decode: impl Fn(B) -> T, encode: impl Fn(&T) -> B)
+  where
+    B: Copy + PartialEq + Debug,
+    T: PartialEq + Debug,
+  {
+    for (bits, expected) in table {
+      let decoded = decode(*bits);
+
+      assert_eq!(&decoded, expected, "{:#x?}", bits);
+      assert_eq!(encode(&decoded), *bits, "{:?}", decoded);
     }
   }
+
+  /// decode a message body and check it encodes back to the same bytes
+  fn round_trip<T>(data: &[u8]) -> T
+  where
+    T: TryFrom<Vec<u8>, Error = DecodeError> + Into<Vec<u8>> + Clone + Debug,
+  {
+    let decoded = T::try_from(data.to_vec()).unwrap_or_else(|err| panic!("could not decode {:02x?}: {}", data, err));
+    assert_eq!(decoded.clone().into(), data, "{:?}", decoded);
+
+    decoded
+  }
+
+  fn flags(tripped: bool, faulted: bool, alarm: bool, trouble: bool, bypassed: bool) -> ZoneStatusFlags {
+    ZoneStatusFlags {
+      tripped,
+      faulted,
+      alarm,
+      trouble,
+      bypassed,
+    }
+  }
+
+  #[test]
+  fn zone_status_bits() {
+    use ZoneStatus::*;
+
+    check_bits(
+      &[
+        (0x00, (flags(false, false, false, false, false), Normal)),
+        (0x01, (flags(true, false, false, false, false), Tripped)),
+        (0x02, (flags(false, true, false, false, false), Faulted)),
+        (0x04, (flags(false, false, true, false, false), Alarm)),
+        (0x08, (flags(false, false, false, true, false), Trouble)),
+        (0x10, (flags(false, false, false, false, true), Bypassed)),
+        (0x11, (flags(true, false, false, false, true), Bypassed)),
+        (0x0C, (flags(false, false, true, true, false), Alarm)),
+        (0x1F, (flags(true, true, true, true, true), Alarm)),
+      ],
+      |bits| (ZoneStatusFlags::from(bits), ZoneStatusFlags::from(bits).primary()),
+      |(decoded, _)| u8::from(*decoded),
+    );
+
+    // the top three bits aren't used
+    assert!(ZoneStatusFlags::from(0xE0).is_normal());
+    assert_eq!(u8::from(ZoneStatusFlags::from(0xE1)), 0x01);
+  }
+
+  #[test]
+  fn zone_status_message() {
+    let data: ZoneStatusData = round_trip(&[0x01, 0x00, 0x00, 0x05, 0x12]);
+
+    assert_eq!(data.zone_id(), "p1-z5");
+    assert_eq!(data.zone_status, flags(false, true, false, false, true));
+
+    // zone numbers take both bytes
+    let data: ZoneStatusData = round_trip(&[0x01, 0x00, 0x01, 0x02, 0x00]);
+    assert_eq!(data.zone_id(), "p1-z258");
+  }
+
+  #[test]
+  fn code_type_numbers() {
+    use CodeType::*;
+
+    check_bits(
+      &[
+        (0x0000_u16, User(0)),
+        (0x00E5, User(229)),
+        (0x00E6, Master(0)),
+        (0x00EF, Duress(1)),
+        (0x00F6, SystemMaster),
+        (0x00FC, System),
+        (0x00FD, Other(0x00FD)),
+        (0x0101, Other(0x0101)),
+      ],
+      CodeType::from,
+      |code| u16::from(*code),
+    );
+
+    // a single byte still decodes the same way
+    assert_eq!(CodeType::from(232_u8), Master(2));
+    assert_eq!(CodeType::from(255_u8), Other(255));
+  }
+
+  #[test]
+  fn feature_bits() {
+    use Feature::*;
+
+    check_bits(
+      &[
+        (0x00, vec![]),
+        (0x01, vec![Chime]),
+        (0x02, vec![EnergySaver]),
+        (0x04, vec![NoDelay]),
+        (0x08, vec![LatchKey]),
+        (0x10, vec![SilentArm]),
+        (0x20, vec![QuickArm]),
+        (0x21, vec![Chime, QuickArm]),
+        (0x3F, Feature::ALL.to_vec()),
+      ]
+      .map(|(bits, features)| (bits, features.into_iter().collect::<HashSet<_>>())),
+      Feature::from_bits,
+      |decoded| Feature::to_bits(decoded),
+    );
+
+    // the top two bits aren't used
+    assert!(Feature::from_bits(0xC0).is_empty());
+    assert_eq!(Feature::to_bits(&Feature::from_bits(0xC4)), 0x04);
+  }
+
+  #[test]
+  fn feature_state_message() {
+    let data: FeatureState = round_trip(&[0x02, 0x00, 0x09]);
+
+    assert_eq!(data.partition_number, 2);
+    assert_eq!(data.features, [Feature::Chime, Feature::LatchKey].into_iter().collect());
+  }
+
+  #[test]
+  fn lights_bits() {
+    check_bits(
+      &[
+        (0x0000, vec![]),
+        (0x0002, vec![1]),
+        (0x0020, vec![5]),
+        (0x0208, vec![3, 9]),
+        (0x01FE, vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        // bit 0 is set whenever every light is on
+        (0x03FF, LightsState::LIGHTS.to_vec()),
+      ]
+      .map(|(bits, lights)| (bits, lights.into_iter().collect::<HashSet<_>>())),
+      LightsState::from_bits,
+      |decoded| LightsState::to_bits(decoded),
+    );
+
+    // bits above light 9 aren't used, and every light on encodes the all bit even if it wasn't set
+    assert!(LightsState::from_bits(0xFC00).is_empty());
+    assert_eq!(LightsState::to_bits(&[0, 10, 16]), 0x0000);
+    assert_eq!(LightsState::to_bits(&LightsState::from_bits(0x03FE)), 0x03FF);
+  }
+
+  #[test]
+  fn lights_state_message() {
+    let data: LightsState = round_trip(&[0x01, 0x00, 0x02, 0x08]);
+
+    assert_eq!(data.lights, [3, 9].into_iter().collect());
+  }
+
+  #[test]
+  fn light_attach_message() {
+    let data: LightAttach = round_trip(&[0x01, 0x00, 0x03, 0x01, 0x02]);
+
+    assert_eq!(data.light_number, 3);
+    assert_eq!(data.zone_id(), "p1-z258");
+  }
+
+  #[test]
+  fn unknown_event_source_round_trips() {
+    let mut data = Vec::<u8>::from(AlarmTrouble {
+      partition_number: 2,
+      area_number: 0,
+      source_type: EventSource::Zone,
+      source_number: (0, 0, 5),
+      event: Event::Alarm(AlarmEventData::Fire),
+    });
+    data[2] = 0x07;
+
+    let alarm: AlarmTrouble = round_trip(&data);
+    assert!(
+      matches!(alarm.source_type, EventSource::Unknown(0x07)),
+      "{:?}",
+      alarm.source_type
+    );
+    assert_eq!(alarm.source(), ConditionSource::Partition { partition_number: 2 });
+  }
 }
@@ -0,0 +1,27 @@
+//! messages the unit tests build their scenarios from
+
+use crate::{
+  communication::RecvMessage,
+  equipment::{AlarmTrouble, ArmingLevel, ArmingLevelData, CodeType, Event, EventSource},
+};
+
+/// a partition changing arming level, as user 1
+pub fn arming_level(partition_number: u8, arming_level: ArmingLevel) -> RecvMessage {
+  RecvMessage::ArmingLevel(ArmingLevelData {
+    partition_number,
+    area_number: 0,
+    user: CodeType::User(1),
+    arming_level,
+  })
+}
+
+/// an alarm or trouble event from zone (or device) 5
+pub fn alarm_trouble(partition_number: u8, source_type: EventSource, event: Event) -> RecvMessage {
+  RecvMessage::AlarmTrouble(AlarmTrouble {
+    partition_number,
+    area_number: 0,
+    source_type,
+    source_number: (0, 0, 5),
+    event,
+  })
+}
@@ -0,0 +1,822 @@
+use std::{
+  collections::VecDeque,
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
+
+use futures::{Stream, StreamExt};
+use tokio::{
+  sync::{broadcast, mpsc},
+  time::{Interval, MissedTickBehavior},
+};
+
+use crate::{
+  commands::{ArmOptions, ConfirmOutcome, DisarmOptions, Keypress, PanicConfirmation, PanicKind},
+  communication::{RecvMessage, SendableMessage},
+  equipment::{AlarmEventData, AlarmTrouble, ArmingLevel, Event, LightsState, ZoneStatusFlags},
+  journal::Journal,
+  serial::{Outgoing, PendingSend, SendOutcome},
+  state::{DelayTick, StateChange},
+  subscription::{Lagged, Subscription},
+  ClientError, ConcordState,
+};
+
+/// A cheap, cloneable handle to a Concord4 connection.
+///
+/// The handle can be moved into other tasks to send commands, read the state and subscribe to panel messages \
+/// while a single task drives the connection. Get one from [crate::Concord4::handle] or [crate::Concord4::spawn].
+///
+/// commands only reach the panel while the connection is being driven, \
+/// either by [crate::Concord4::recv] or by the task started with [crate::Concord4::spawn]
+#[derive(Debug, Clone)]
+pub struct Concord4Handle {
+  /// The current state of the server. The state is Send + Sync, so it can be shared between threads.
+  pub state: ConcordState,
+
+  // internal
+  tx: mpsc::Sender<Outgoing>,
+  // only the connection holds strong senders, so subscriptions end when it goes away
+  events: broadcast::WeakSender<RecvMessage>,
+  changes: broadcast::WeakSender<StateChange>,
+  // shared, so handles taken before a journal is attached still see it
+  journal: Arc<OnceLock<Journal>>,
+}
+
+impl Concord4Handle {
+  pub(crate) fn new(
+    state: ConcordState,
+    tx: mpsc::Sender<Outgoing>,
+    events: broadcast::WeakSender<RecvMessage>,
+    changes: broadcast::WeakSender<StateChange>,
+  ) -> Self {
+    Self {
+      state,
+      tx,
+      events,
+      changes,
+      journal: Arc::default(),
+    }
+  }
+
+  /// attach the journal every handle to this connection returns, unless one is attached already
+  pub(crate) fn set_journal(&self, journal: Journal) {
+    if self.journal.set(journal).is_err() {
+      tracing::warn!(target: "concord4::handle", "a journal is already attached, keeping the first one");
+    }
+  }
+
+  /// subscribe to every message received from the panel
+  ///
+  /// each subscriber gets its own copy of every message, so they can't steal from each other or from [crate::Concord4::recv]
+  ///
+  /// # returns
+  /// a [Subscription] that yields each [RecvMessage] received after this call, \
+  /// or reports [Lagged] if the subscriber falls too far behind
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// let mut messages = client.subscribe();
+  /// while let Some(message) = messages.recv().await {
+  ///   match message {
+  ///     Ok(message) => println!("{:?}", message),
+  ///     Err(Lagged(missed)) => println!("missed {} messages", missed),
+  ///   }
+  /// }
+  /// # }
+  /// ```
+  pub fn subscribe(&self) -> Subscription<RecvMessage> {
+    match self.events.upgrade() {
+      Some(events) => Subscription::new(events.subscribe()),
+      None => Subscription::closed(),
+    }
+  }
+
+  /// subscribe to changes in the panel state
+  ///
+  /// unlike [Concord4Handle::subscribe], only messages that actually changed something are reported, \
+  /// and [Concord4Handle::state] already reflects the change by the time it arrives
+  ///
+  /// # returns
+  /// a [Subscription] that yields each [StateChange] made after this call
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// let mut changes = client.subscribe_changes();
+  /// while let Some(Ok(change)) = changes.recv().await {
+  ///   if let StateChange::ZoneStatusChanged { zone_id, new, .. } = change {
+  ///     println!("{} is now {:?}", zone_id, new);
+  ///   }
+  /// }
+  /// # }
+  /// ```
+  pub fn subscribe_changes(&self) -> Subscription<StateChange> {
+    match self.changes.upgrade() {
+      Some(changes) => Subscription::new(changes.subscribe()),
+      None => Subscription::closed(),
+    }
+  }
+
+  /// the history of alarm/trouble events and arming level changes, if the connection keeps one
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// if let Some(journal) = client.journal() {
+  ///   let entries = journal.query_async(JournalQuery::new().partition(1)).await.expect("could not read journal");
+  ///   for entry in entries {
+  ///     println!("{:?} {:?} by {:?}", entry.time, entry.kind, entry.user);
+  ///   }
+  /// }
+  /// # }
+  /// ```
+  pub fn journal(&self) -> Option<&Journal> {
+    self.journal.get()
+  }
+
+  /// count down every entry and exit delay, once a second
+  ///
+  /// the countdowns themselves are in [crate::ConcordStateInner::delays]; \
+  /// subscribe to changes to hear about [StateChange::DelayStarted] and [StateChange::DelayEnded]
+  ///
+  /// # returns
+  /// a [Stream] that yields a [DelayTick] for each partition with a delay running, every second, \
+  /// and ends once the connection is closed; a delay that runs out is ended here if the panel hasn't ended it
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use futures::StreamExt;
+  /// # async fn example(client: Concord4Handle) {
+  /// let mut ticks = Box::pin(client.delay_ticks());
+  /// while let Some(tick) = ticks.next().await {
+  ///   println!("{:?} delay: {}s", tick.direction, tick.remaining);
+  /// }
+  /// # }
+  /// ```
+  pub fn delay_ticks(&self) -> impl Stream<Item = DelayTick> + Send + 'static {
+    let state = self.state.clone();
+    let changes = self.changes.clone();
+
+    // the interval is made on first poll, since it needs a runtime
+    futures::stream::unfold((None::<Interval>, VecDeque::new()), move |(interval, mut pending)| {
+      let state = state.clone();
+      let changes = changes.clone();
+
+      async move {
+        let mut interval = interval.unwrap_or_else(|| {
+          let mut interval = tokio::time::interval(Duration::from_secs(1));
+          interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+          interval
+        });
+
+        loop {
+          if let Some(tick) = pending.pop_front() {
+            return Some((tick, (Some(interval), pending)));
+          }
+
+          interval.tick().await;
+
+          // only the connection holds a strong sender, so this ends the stream once it's gone
+          let changes = changes.upgrade()?;
+
+          // a delay whose end frame was lost would otherwise tick at 0 until the next message from the panel
+          for change in state.expire_delays() {
+            let _ = changes.send(change);
+          }
+
+          pending.extend(state.delays.iter().map(|delay| DelayTick {
+            partition: *delay.key(),
+            direction: delay.direction,
+            remaining: delay.remaining_secs(),
+          }));
+        }
+      }
+    })
+  }
+
+  /// send a raw command to the Concord4 panel
+  ///
+  /// # args
+  /// `command`: [SendableMessage] - the command to send
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// let pending = client.send(SendableMessage::List(ListRequest::AllData)).await.expect("could not send command");
+  /// assert_eq!(pending.await, SendOutcome::Acked);
+  /// # }
+  /// ```
+  pub async fn send(&self, message: SendableMessage) -> Result<PendingSend, ClientError> {
+    let (outgoing, pending) = Outgoing::new(message);
+    self.tx.send(outgoing).await?;
+
+    Ok(pending)
+  }
+
+  /// arm the alarm
+  ///
+  /// # args
+  /// `options`: [ArmOptions] - the options for arming the alarm
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client.arm(ArmOptions {
+  ///   mode: ArmMode::Stay,
+  ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+  ///   level: Some(ArmLevel::Instant),
+  ///   partition: Some(1),
+  /// }).await.expect("could not arm alarm");
+  /// # }
+  /// ```
+  pub async fn arm(&self, options: ArmOptions) -> Result<PendingSend, ClientError> {
+    let partition = options.partition.unwrap_or(1);
+    if let Some(partition_data) = self.state.partitions.get(&partition) {
+      if partition_data.arming_level != ArmingLevel::Off {
+        return Err(ClientError::Armed);
+      }
+    }
+
+    self.send(SendableMessage::Arm(options)).await
+  }
+
+  /// disarm the alarm
+  ///
+  /// # args
+  /// `options`: [DisarmOptions] - the options for disarming the alarm
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client.disarm(DisarmOptions {
+  ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+  ///   partition: Some(1),
+  /// }).await.expect("could not disarm alarm");
+  /// # }
+  /// ```
+  pub async fn disarm(&self, options: DisarmOptions) -> Result<PendingSend, ClientError> {
+    self.send(SendableMessage::Disarm(options)).await
+  }
+
+  /// arm the alarm and wait for the panel to report the partition as armed in the requested mode
+  ///
+  /// # args
+  /// `options`: [ArmOptions] - the options for arming the alarm \
+  /// `timeout`: [Duration] - how long to wait for the arming level to change
+  ///
+  /// # returns
+  /// a [ConfirmOutcome] describing how the panel reacted, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(client: Concord4Handle) {
+  /// let outcome = client.arm_and_confirm(ArmOptions {
+  ///   mode: ArmMode::Away,
+  ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+  ///   level: None,
+  ///   partition: Some(1),
+  /// }, Duration::from_secs(10)).await.expect("could not arm alarm");
+  /// # }
+  /// ```
+  pub async fn arm_and_confirm(&self, options: ArmOptions, timeout: Duration) -> Result<ConfirmOutcome, ClientError> {
+    let partition = options.partition.unwrap_or(1);
+    let level = options.mode.arming_level();
+
+    // subscribe before sending so the arming level can't slip past
+    let messages = self.subscribe();
+    let pending = self.arm(options).await?;
+
+    confirm(skip_lagged(messages), pending, timeout, |message| {
+      confirmation(partition, message, level)
+    })
+    .await
+  }
+
+  /// disarm the alarm and wait for the panel to report the partition as disarmed
+  ///
+  /// # args
+  /// `options`: [DisarmOptions] - the options for disarming the alarm \
+  /// `timeout`: [Duration] - how long to wait for the arming level to change
+  ///
+  /// # returns
+  /// a [ConfirmOutcome] describing how the panel reacted, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(client: Concord4Handle) {
+  /// let outcome = client.disarm_and_confirm(DisarmOptions {
+  ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+  ///   partition: Some(1),
+  /// }, Duration::from_secs(10)).await.expect("could not disarm alarm");
+  /// # }
+  /// ```
+  pub async fn disarm_and_confirm(
+    &self,
+    options: DisarmOptions,
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome, ClientError> {
+    let partition = options.partition.unwrap_or(1);
+
+    let messages = self.subscribe();
+    let pending = self.disarm(options).await?;
+
+    confirm(skip_lagged(messages), pending, timeout, |message| {
+      confirmation(partition, message, ArmingLevel::Off)
+    })
+    .await
+  }
+
+  /// toggle the chime on the alarm
+  ///
+  /// # args
+  /// `partition`: [Option<u8>] - the partition to toggle the chime on, or None for partition 1
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client.toggle_chime(Some(1)).await.expect("could not toggle chime");
+  /// # }
+  /// ```
+  pub async fn toggle_chime(&self, partition: Option<u8>) -> Result<PendingSend, ClientError> {
+    let partition = partition.unwrap_or(1);
+
+    if let Some(partition_data) = self.state.partitions.get(&partition) {
+      if partition_data.arming_level != ArmingLevel::Off {
+        return Err(ClientError::Armed);
+      }
+    }
+
+    self.send(SendableMessage::ToggleChime(Some(partition))).await
+  }
+
+  /// bypass a zone, so it is ignored until it is unbypassed or the partition is disarmed
+  ///
+  /// the zone must be one the panel has reported, and must not already be bypassed, \
+  /// since the panel uses the same keypresses to bypass and unbypass a zone
+  ///
+  /// # args
+  /// `zone_id`: [&str] - the id of the zone in [crate::ConcordStateInner::zones], e.g. `p1-z5` \
+  /// `code`: [[Keypress; 4]] - a user code allowed to bypass zones
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client
+  ///   .bypass_zone("p1-z5", [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four])
+  ///   .await
+  ///   .expect("could not bypass zone");
+  /// # }
+  /// ```
+  pub async fn bypass_zone(&self, zone_id: &str, code: [Keypress; 4]) -> Result<PendingSend, ClientError> {
+    let zone = self.bypass_target(zone_id, true)?;
+    self.send_bypass(&zone, code).await
+  }
+
+  /// unbypass a zone that was bypassed, see [Concord4Handle::bypass_zone]
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client
+  ///   .unbypass_zone("p1-z5", [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four])
+  ///   .await
+  ///   .expect("could not unbypass zone");
+  /// # }
+  /// ```
+  pub async fn unbypass_zone(&self, zone_id: &str, code: [Keypress; 4]) -> Result<PendingSend, ClientError> {
+    let zone = self.bypass_target(zone_id, false)?;
+    self.send_bypass(&zone, code).await
+  }
+
+  /// bypass a zone and wait for the panel to report it as bypassed
+  ///
+  /// # args
+  /// `zone_id`: [&str] - the id of the zone in [crate::ConcordStateInner::zones], e.g. `p1-z5` \
+  /// `code`: [[Keypress; 4]] - a user code allowed to bypass zones \
+  /// `timeout`: [Duration] - how long to wait for the zone status to change
+  ///
+  /// # returns
+  /// a [ConfirmOutcome] with the zone's new status if the panel confirmed it, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(client: Concord4Handle) {
+  /// let outcome = client
+  ///   .bypass_zone_and_confirm("p1-z5", [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four], Duration::from_secs(5))
+  ///   .await
+  ///   .expect("could not bypass zone");
+  /// # }
+  /// ```
+  pub async fn bypass_zone_and_confirm(
+    &self,
+    zone_id: &str,
+    code: [Keypress; 4],
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<ZoneStatusFlags>, ClientError> {
+    let zone = self.bypass_target(zone_id, true)?;
+
+    let messages = self.subscribe();
+    let pending = self.send_bypass(&zone, code).await?;
+
+    confirm(skip_lagged(messages), pending, timeout, |message| {
+      bypass_confirmation(&zone, message, true)
+    })
+    .await
+  }
+
+  /// unbypass a zone and wait for the panel to report it as no longer bypassed, \
+  /// see [Concord4Handle::bypass_zone_and_confirm]
+  pub async fn unbypass_zone_and_confirm(
+    &self,
+    zone_id: &str,
+    code: [Keypress; 4],
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<ZoneStatusFlags>, ClientError> {
+    let zone = self.bypass_target(zone_id, false)?;
+
+    let messages = self.subscribe();
+    let pending = self.send_bypass(&zone, code).await?;
+
+    confirm(skip_lagged(messages), pending, timeout, |message| {
+      bypass_confirmation(&zone, message, false)
+    })
+    .await
+  }
+
+  /// raise a panic alarm on a partition, as if its panic buttons were pressed, and wait for the panel to report it
+  ///
+  /// **this calls for help**: the panel reports the alarm to the central station like any other panic, \
+  /// so `confirmation` has to be made for the same `kind` of panic
+  ///
+  /// # args
+  /// `kind`: [PanicKind] - which panic to raise \
+  /// `partition`: [u8] - the partition to raise it on \
+  /// `confirmation`: [PanicConfirmation] - made with [PanicConfirmation::confirm] for the same `kind` \
+  /// `timeout`: [Duration] - how long to wait for the panel to report the alarm
+  ///
+  /// # returns
+  /// a [ConfirmOutcome] with the [AlarmTrouble] the panel reported, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(client: Concord4Handle) {
+  /// let outcome = client
+  ///   .trigger_panic(
+  ///     PanicKind::Auxiliary,
+  ///     1,
+  ///     PanicConfirmation::confirm(PanicKind::Auxiliary),
+  ///     Duration::from_secs(10),
+  ///   )
+  ///   .await
+  ///   .expect("could not trigger panic");
+  ///
+  /// if let ConfirmOutcome::Confirmed(alarm) = outcome {
+  ///   println!("panel reported {:?}", alarm.event);
+  /// }
+  /// # }
+  /// ```
+  pub async fn trigger_panic(
+    &self,
+    kind: PanicKind,
+    partition: u8,
+    confirmation: PanicConfirmation,
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<AlarmTrouble>, ClientError> {
+    let messages = self.subscribe();
+    let pending = self.send_panic(kind, partition, confirmation).await?;
+
+    confirm(skip_lagged(messages), pending, timeout, |message| {
+      panic_confirmation(kind, partition, message)
+    })
+    .await
+  }
+
+  /// the panel only raises a panic when the key is pressed twice in a row, like holding down both panic buttons
+  pub(crate) async fn send_panic(
+    &self,
+    kind: PanicKind,
+    partition: u8,
+    confirmation: PanicConfirmation,
+  ) -> Result<PendingSend, ClientError> {
+    if confirmation.kind() != kind {
+      return Err(ClientError::PanicNotConfirmed);
+    }
+
+    tracing::warn!(target: "concord4::handle", "triggering {:?} panic on partition {}", kind, partition);
+
+    let key = Keypress::from(kind);
+    self.send(SendableMessage::Keypress(partition, vec![key, key])).await
+  }
+
+  /// turn one of a partition's lights on or off
+  ///
+  /// # args
+  /// `partition`: [u8] - the partition the light belongs to \
+  /// `light`: [u8] - the light number, 1 to 9 \
+  /// `on`: [bool] - whether to turn the light on or off
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client.set_light(1, 3, true).await.expect("could not turn on light");
+  /// # }
+  /// ```
+  pub async fn set_light(&self, partition: u8, light: u8, on: bool) -> Result<PendingSend, ClientError> {
+    if !LightsState::LIGHTS.contains(&light) {
+      return Err(ClientError::InvalidLight(light));
+    }
+
+    self.send_lights(partition, light, on).await
+  }
+
+  /// turn all of a partition's lights on or off
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// client.all_lights(1, false).await.expect("could not turn off lights");
+  /// # }
+  /// ```
+  pub async fn all_lights(&self, partition: u8, on: bool) -> Result<PendingSend, ClientError> {
+    self.send_lights(partition, 0, on).await
+  }
+
+  /// the lights on or off key followed by the light number, where 0 is every light
+  async fn send_lights(&self, partition: u8, light: u8, on: bool) -> Result<PendingSend, ClientError> {
+    let mut keys = vec![if on { Keypress::LightsOn } else { Keypress::LightsOff }];
+    keys.extend(Keypress::digits(light.into(), 1));
+
+    self.send(SendableMessage::Keypress(partition, keys)).await
+  }
+
+  /// look up a zone that is about to be bypassed (or unbypassed, when `bypass` is false)
+  pub(crate) fn bypass_target(&self, zone_id: &str, bypass: bool) -> Result<BypassTarget, ClientError> {
+    let zone = self
+      .state
+      .zones
+      .get(zone_id)
+      .ok_or_else(|| ClientError::UnknownZone(zone_id.to_string()))?;
+
+    match (bypass, zone.zone_status.bypassed) {
+      (true, true) => Err(ClientError::ZoneBypassed(zone_id.to_string())),
+      (false, false) => Err(ClientError::ZoneNotBypassed(zone_id.to_string())),
+      _ => Ok(BypassTarget {
+        partition_number: zone.partition_number,
+        zone_number: zone.zone_number,
+      }),
+    }
+  }
+
+  /// `# 7 code zone #` toggles the zone's bypass
+  pub(crate) async fn send_bypass(&self, zone: &BypassTarget, code: [Keypress; 4]) -> Result<PendingSend, ClientError> {
+    let mut keys = vec![Keypress::Pound, Keypress::Seven];
+    keys.extend_from_slice(&code);
+    keys.extend(Keypress::digits(zone.zone_number, 2));
+    keys.push(Keypress::Pound);
+
+    self.send(SendableMessage::Keypress(zone.partition_number, keys)).await
+  }
+
+  /// stop the siren on a partition by disarming it
+  ///
+  /// the panel has no separate silence sequence: it silences a sounding siren when a code is disarmed, \
+  /// so this **disarms the partition** (if it was armed) through [Concord4Handle::disarm]; \
+  /// use [Concord4Handle::disarm_and_confirm] to wait for the panel to report it disarmed
+  ///
+  /// # args
+  /// `options`: [DisarmOptions] - the code to enter and the partition the siren belongs to
+  ///
+  /// # returns
+  /// a [PendingSend] that resolves once the panel responds if the command was queued successfully, \
+  /// or a [ClientError] if there was an error
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(client: Concord4Handle) {
+  /// if client.state.sirens.get(&1).is_some_and(|siren| matches!(*siren, SirenState::Sounding { .. })) {
+  ///   client.stop_siren(DisarmOptions {
+  ///     code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+  ///     partition: Some(1),
+  ///   }).await.expect("could not stop siren");
+  /// }
+  /// # }
+  /// ```
+  pub async fn stop_siren(&self, options: DisarmOptions) -> Result<PendingSend, ClientError> {
+    self.disarm(options).await
+  }
+}
+
+/// whether `message` settles a bypass or unbypass of `zone`
+pub(crate) fn bypass_confirmation(
+  zone: &BypassTarget,
+  message: &RecvMessage,
+  bypassed: bool,
+) -> Option<ConfirmOutcome<ZoneStatusFlags>> {
+  match message {
+    RecvMessage::ZoneStatus(data)
+      if data.partition_number == zone.partition_number
+        && data.zone_number == zone.zone_number
+        && data.zone_status.bypassed == bypassed =>
+    {
+      Some(ConfirmOutcome::Confirmed(data.zone_status))
+    }
+    RecvMessage::Touchpad(display) if display.partition_number == zone.partition_number && display.is_rejection() => {
+      Some(ConfirmOutcome::Rejected(display.clone()))
+    }
+    _ => None,
+  }
+}
+
+/// whether `message` is the alarm raised by a `kind` panic on `partition`
+pub(crate) fn panic_confirmation(
+  kind: PanicKind,
+  partition: u8,
+  message: &RecvMessage,
+) -> Option<ConfirmOutcome<AlarmTrouble>> {
+  match message {
+    RecvMessage::AlarmTrouble(data) if data.partition_number == partition => match (kind, &data.event) {
+      (PanicKind::Police, Event::Alarm(AlarmEventData::PolicePanic))
+      | (PanicKind::Auxiliary, Event::Alarm(AlarmEventData::AuxiliaryPanic))
+      | (PanicKind::Fire, Event::Alarm(AlarmEventData::FirePanic)) => Some(ConfirmOutcome::Confirmed(data.clone())),
+      _ => None,
+    },
+    RecvMessage::Touchpad(display) if display.partition_number == partition && display.is_rejection() => {
+      Some(ConfirmOutcome::Rejected(display.clone()))
+    }
+    _ => None,
+  }
+}
+
+/// the zone a bypass or unbypass is aimed at
+pub(crate) struct BypassTarget {
+  pub(crate) partition_number: u8,
+  pub(crate) zone_number: u16,
+}
+
+/// whether `message` settles an arm/disarm of `partition` to the `expected` level
+pub(crate) fn confirmation(partition: u8, message: &RecvMessage, expected: ArmingLevel) -> Option<ConfirmOutcome> {
+  match message {
+    RecvMessage::ArmingLevel(data) if data.partition_number == partition && data.arming_level == expected => {
+      Some(ConfirmOutcome::Confirmed(data.arming_level))
+    }
+    RecvMessage::Touchpad(display) if display.partition_number == partition && display.is_rejection() => {
+      Some(ConfirmOutcome::Rejected(display.clone()))
+    }
+    _ => None,
+  }
+}
+
+/// wait for the panel to settle a command, as decided by `settles`
+///
+/// `messages` is whatever gets to see the panel's messages: a subscription for a [Concord4Handle], \
+/// or the connection itself for a [crate::Concord4] that is driving it while it waits
+pub(crate) async fn confirm<T>(
+  messages: impl Stream<Item = Result<RecvMessage, ClientError>>,
+  mut pending: PendingSend,
+  timeout: Duration,
+  settles: impl Fn(&RecvMessage) -> Option<ConfirmOutcome<T>>,
+) -> Result<ConfirmOutcome<T>, ClientError> {
+  futures::pin_mut!(messages);
+
+  let deadline = tokio::time::sleep(timeout);
+  tokio::pin!(deadline);
+
+  let mut delivered = false;
+
+  loop {
+    // the ACK is resolved before anything after it is received or published, so checking it first keeps the order
+    tokio::select! {
+      biased;
+      _ = &mut deadline => return Ok(ConfirmOutcome::Timeout),
+      outcome = &mut pending, if !delivered => match outcome {
+        SendOutcome::Acked => delivered = true,
+        outcome => return Ok(ConfirmOutcome::NotDelivered(outcome)),
+      },
+      message = messages.next() => match message {
+        // anything before the ACK (a stale display, another keypad) isn't a response to the command
+        Some(Ok(message)) if delivered => {
+          if let Some(outcome) = settles(&message) {
+            return Ok(outcome);
+          }
+        }
+        Some(Ok(_)) => {}
+        Some(Err(err)) => return Err(err),
+        None => return Err(ClientError::SerialPortClosed),
+      },
+    }
+  }
+}
+
+/// the messages a subscription sees, skipping over any it missed by lagging behind
+fn skip_lagged(messages: Subscription<RecvMessage>) -> impl Stream<Item = Result<RecvMessage, ClientError>> {
+  messages.filter_map(|message| {
+    futures::future::ready(match message {
+      Ok(message) => Some(Ok(message)),
+      Err(Lagged(missed)) => {
+        tracing::warn!(target: "concord4::handle", "missed {} messages while waiting for confirmation", missed);
+        None
+      }
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{decode, fixtures::arming_level, touchpad::TouchpadDisplay};
+
+  fn display(partition_number: u8, text: &str) -> RecvMessage {
+    RecvMessage::Touchpad(TouchpadDisplay {
+      partition_number,
+      area_number: 0,
+      message_type: 0,
+      display_tokens: decode::encode_text_tokens(text),
+      text: format!("{} ", text),
+    })
+  }
+
+  #[test]
+  fn invalid_code_rejects() {
+    let outcome = confirmation(1, &display(1, "INVALID CODE"), ArmingLevel::Away);
+
+    assert!(matches!(outcome, Some(ConfirmOutcome::Rejected(_))), "{:?}", outcome);
+  }
+
+  #[test]
+  fn status_lines_do_not_reject() {
+    for text in ["AC POWER FAILURE", "SYSTEM ERROR", "ZONE 5 OPEN"] {
+      let outcome = confirmation(1, &display(1, text), ArmingLevel::Away);
+
+      assert!(outcome.is_none(), "{}: {:?}", text, outcome);
+    }
+  }
+
+  #[test]
+  fn other_partitions_are_ignored() {
+    assert!(confirmation(1, &display(2, "INVALID CODE"), ArmingLevel::Away).is_none());
+    assert!(confirmation(1, &arming_level(2, ArmingLevel::Away), ArmingLevel::Away).is_none());
+  }
+
+  #[test]
+  fn only_the_requested_level_confirms() {
+    let outcome = confirmation(1, &arming_level(1, ArmingLevel::Home), ArmingLevel::Home);
+    assert!(
+      matches!(outcome, Some(ConfirmOutcome::Confirmed(ArmingLevel::Home))),
+      "{:?}",
+      outcome
+    );
+
+    assert!(confirmation(1, &arming_level(1, ArmingLevel::Away), ArmingLevel::Home).is_none());
+    assert!(confirmation(1, &arming_level(1, ArmingLevel::Off), ArmingLevel::Home).is_none());
+  }
+}
@@ -0,0 +1,597 @@
+use std::{
+  collections::{HashSet, VecDeque},
+  fmt, io,
+  sync::{
+    mpsc::{self, SyncSender, TrySendError},
+    Arc, Mutex,
+  },
+  time::SystemTime,
+};
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use std::{
+  fs::{File, OpenOptions},
+  io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  communication::RecvMessage,
+  consts,
+  equipment::{CodeType, ConditionSource, Event},
+};
+
+/// What happened in a [JournalEntry].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum JournalKind {
+  Alarm,
+  AlarmCancel,
+  AlarmRestoral,
+  /// a fire, nonfire or system trouble
+  Trouble,
+  TroubleRestoral,
+  Bypass,
+  Unbypass,
+  Opening,
+  Closing,
+  /// a partition's arming level changed
+  ArmingLevel,
+  /// a partition configuration change, partition event or test
+  Partition,
+  /// a system configuration change or system event
+  System,
+  /// an event with a general type this crate doesn't know about
+  Unknown,
+}
+
+impl From<&Event> for JournalKind {
+  fn from(event: &Event) -> Self {
+    match event {
+      Event::Alarm(_) => JournalKind::Alarm,
+      Event::AlarmCancel(_) => JournalKind::AlarmCancel,
+      Event::AlarmRestoral(_) => JournalKind::AlarmRestoral,
+      Event::FireTrouble(_) | Event::NonfireTrouble(_) | Event::SystemTrouble(_) => JournalKind::Trouble,
+      Event::FireTroubleRestoral(_) | Event::NonfireTroubleRestoral(_) | Event::SystemTroubleRestoral(_) => {
+        JournalKind::TroubleRestoral
+      }
+      Event::Bypass(_) => JournalKind::Bypass,
+      Event::Unbypass(_) => JournalKind::Unbypass,
+      Event::Opening(_) => JournalKind::Opening,
+      Event::Closing(_) => JournalKind::Closing,
+      Event::PartitionConfig(_) | Event::Partition(_) | Event::PartitionTest(_) => JournalKind::Partition,
+      Event::SystemConfigChange(_) | Event::System(_) => JournalKind::System,
+      Event::Unknown(_) => JournalKind::Unknown,
+    }
+  }
+}
+
+/// One event from the panel, as recorded by a [Journal].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct JournalEntry {
+  /// when the client received the event
+  pub time: SystemTime,
+  pub kind: JournalKind,
+  pub partition: u8,
+  /// what the event is about, for alarm and trouble events
+  pub source: Option<ConditionSource>,
+  /// who caused the event, when the panel reports it
+  pub user: Option<CodeType>,
+  /// the message the entry was made from
+  pub message: RecvMessage,
+}
+
+impl JournalEntry {
+  /// make an entry for the messages worth keeping: alarm/trouble events and arming level changes
+  pub fn from_message(time: SystemTime, message: &RecvMessage) -> Option<Self> {
+    let (kind, partition, source, user) = match message {
+      RecvMessage::AlarmTrouble(data) => (
+        JournalKind::from(&data.event),
+        data.partition_number,
+        Some(data.source()),
+        data.event.user(),
+      ),
+      RecvMessage::ArmingLevel(data) => (JournalKind::ArmingLevel, data.partition_number, None, Some(data.user)),
+      _ => return None,
+    };
+
+    Some(Self {
+      time,
+      kind,
+      partition,
+      source,
+      user,
+      message: message.clone(),
+    })
+  }
+}
+
+/// Which [JournalEntry]s to return from [Journal::query]. Every filter left unset matches everything.
+///
+/// # example
+/// ```no_run
+/// # use concord4::*;
+/// # use std::time::{Duration, SystemTime};
+/// # fn example(journal: &Journal) {
+/// let last_day = JournalQuery::new()
+///   .since(SystemTime::now() - Duration::from_secs(24 * 60 * 60))
+///   .partition(1)
+///   .kind(JournalKind::Alarm)
+///   .kind(JournalKind::AlarmRestoral);
+///
+/// let alarms = journal.query(&last_day).expect("could not read journal");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JournalQuery {
+  since: Option<SystemTime>,
+  until: Option<SystemTime>,
+  partition: Option<u8>,
+  kinds: HashSet<JournalKind>,
+}
+
+impl JournalQuery {
+  /// a query matching every entry
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// only entries recorded at or after `time`
+  pub fn since(mut self, time: SystemTime) -> Self {
+    self.since = Some(time);
+    self
+  }
+
+  /// only entries recorded before `time`
+  pub fn until(mut self, time: SystemTime) -> Self {
+    self.until = Some(time);
+    self
+  }
+
+  /// only entries for one partition
+  pub fn partition(mut self, partition: u8) -> Self {
+    self.partition = Some(partition);
+    self
+  }
+
+  /// only entries of this kind, or any other kind added the same way
+  pub fn kind(mut self, kind: JournalKind) -> Self {
+    self.kinds.insert(kind);
+    self
+  }
+
+  pub fn matches(&self, entry: &JournalEntry) -> bool {
+    self.since.map_or(true, |since| entry.time >= since)
+      && self.until.map_or(true, |until| entry.time < until)
+      && self.partition.map_or(true, |partition| entry.partition == partition)
+      && (self.kinds.is_empty() || self.kinds.contains(&entry.kind))
+  }
+}
+
+/// Somewhere to keep a [Journal]'s entries.
+///
+/// `append` is called from the journal's own writer thread and `query` from whoever queries the journal, \
+/// so both may block (on a file, a database, ...) without holding up the connection
+pub trait JournalBackend: Send + Sync + 'static {
+  /// store a new entry; entries arrive oldest first
+  fn append(&self, entry: &JournalEntry) -> io::Result<()>;
+
+  /// every stored entry matching `query`, oldest first
+  fn query(&self, query: &JournalQuery) -> io::Result<Vec<JournalEntry>>;
+}
+
+/// A history of the alarm/trouble events and arming level changes the panel reported.
+///
+/// attach one with [crate::Concord4Builder::journal] or [crate::Concord4::with_journal] \
+/// and read it back from [crate::Concord4Handle::journal]. \
+/// entries are written on a thread of their own, so a new entry can take a moment to show up in queries
+#[derive(Clone)]
+pub struct Journal {
+  backend: Arc<dyn JournalBackend>,
+  // None if the writer thread couldn't be started, in which case entries are written as they're recorded
+  writer: Option<SyncSender<JournalEntry>>,
+}
+
+impl fmt::Debug for Journal {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Journal").finish_non_exhaustive()
+  }
+}
+
+impl Journal {
+  pub fn new(backend: impl JournalBackend) -> Self {
+    let backend: Arc<dyn JournalBackend> = Arc::new(backend);
+    let (writer, entries) = mpsc::sync_channel::<JournalEntry>(consts::JOURNAL_QUEUE_CAPACITY);
+
+    // the thread ends once every clone of the journal, and so every sender, is gone
+    let writing = backend.clone();
+    let spawned = std::thread::Builder::new()
+      .name("concord4-journal".to_string())
+      .spawn(move || {
+        for entry in entries {
+          append(writing.as_ref(), &entry);
+        }
+      });
+
+    let writer = match spawned {
+      Ok(_) => Some(writer),
+      Err(err) => {
+        tracing::error!(target: "concord4::journal", "could not start the journal writer, writing entries inline: {}", err);
+        None
+      }
+    };
+
+    Self { backend, writer }
+  }
+
+  /// every recorded entry matching `query`, oldest first
+  ///
+  /// this reads the backend on the calling thread, which blocks for backends like [JsonLinesJournal]; \
+  /// use [Journal::query_async] from async code
+  pub fn query(&self, query: &JournalQuery) -> io::Result<Vec<JournalEntry>> {
+    self.backend.query(query)
+  }
+
+  /// every recorded entry matching `query`, oldest first, read on tokio's blocking thread pool
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(journal: &Journal) {
+  /// let alarms = journal
+  ///   .query_async(JournalQuery::new().kind(JournalKind::Alarm))
+  ///   .await
+  ///   .expect("could not read journal");
+  /// # }
+  /// ```
+  pub async fn query_async(&self, query: JournalQuery) -> io::Result<Vec<JournalEntry>> {
+    let backend = self.backend.clone();
+
+    tokio::task::spawn_blocking(move || backend.query(&query))
+      .await
+      .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+  }
+
+  /// record a message if it's one worth keeping
+  pub(crate) fn record(&self, message: &RecvMessage) {
+    let Some(entry) = JournalEntry::from_message(SystemTime::now(), message) else {
+      return;
+    };
+
+    let Some(writer) = &self.writer else {
+      append(self.backend.as_ref(), &entry);
+      return;
+    };
+
+    // losing history is bad, but not bad enough to stop talking to the panel
+    match writer.try_send(entry) {
+      Ok(()) => {}
+      Err(TrySendError::Full(entry)) => {
+        tracing::warn!(target: "concord4::journal", "journal writer is behind, dropping {:?}", entry.kind);
+      }
+      Err(TrySendError::Disconnected(entry)) => {
+        tracing::warn!(target: "concord4::journal", "journal writer stopped, dropping {:?}", entry.kind);
+      }
+    }
+  }
+}
+
+fn append(backend: &dyn JournalBackend, entry: &JournalEntry) {
+  if let Err(err) = backend.append(entry) {
+    tracing::warn!(target: "concord4::journal", "could not record {:?}: {}", entry.kind, err);
+  }
+}
+
+/// A [JournalBackend] that keeps the most recent entries in memory.
+#[derive(Debug)]
+pub struct MemoryJournal {
+  capacity: usize,
+  entries: Mutex<VecDeque<JournalEntry>>,
+}
+
+impl MemoryJournal {
+  /// keep at most `capacity` entries, dropping the oldest to make room
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      capacity: capacity.max(1),
+      entries: Mutex::new(VecDeque::new()),
+    }
+  }
+}
+
+impl JournalBackend for MemoryJournal {
+  fn append(&self, entry: &JournalEntry) -> io::Result<()> {
+    let mut entries = self
+      .entries
+      .lock()
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "journal lock poisoned"))?;
+
+    if entries.len() == self.capacity {
+      entries.pop_front();
+    }
+    entries.push_back(entry.clone());
+
+    Ok(())
+  }
+
+  fn query(&self, query: &JournalQuery) -> io::Result<Vec<JournalEntry>> {
+    let entries = self
+      .entries
+      .lock()
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "journal lock poisoned"))?;
+
+    Ok(entries.iter().filter(|entry| query.matches(entry)).cloned().collect())
+  }
+}
+
+/// A [JournalBackend] that appends every entry to a file as one line of json.
+///
+/// each line also carries the raw message so it can be decoded again; \
+/// queries read the whole file, so rotate it if it grows too large
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct JsonLinesJournal {
+  path: PathBuf,
+  file: Mutex<File>,
+}
+
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct JournalLine<'a> {
+  #[serde(flatten)]
+  entry: &'a JournalEntry,
+  raw: Vec<u8>,
+}
+
+#[cfg(feature = "json")]
+#[derive(Deserialize)]
+struct StoredLine {
+  time: SystemTime,
+  raw: Vec<u8>,
+}
+
+#[cfg(feature = "json")]
+impl JsonLinesJournal {
+  /// open the file for appending, creating it if it doesn't exist
+  pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    let mut file = OpenOptions::new().create(true).read(true).append(true).open(&path)?;
+
+    // a crash can leave a line without its newline, and the next entry would be appended onto it
+    if file.metadata()?.len() > 0 {
+      let mut last = [0];
+      file.seek(SeekFrom::End(-1))?;
+      file.read_exact(&mut last)?;
+
+      if last != *b"\n" {
+        file.write_all(b"\n")?;
+      }
+    }
+
+    Ok(Self {
+      path,
+      file: Mutex::new(file),
+    })
+  }
+}
+
+#[cfg(feature = "json")]
+impl JournalBackend for JsonLinesJournal {
+  fn append(&self, entry: &JournalEntry) -> io::Result<()> {
+    let raw = Vec::<u8>::try_from(entry.message.clone())
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "message has no raw form"))?;
+
+    let mut line = serde_json::to_vec(&JournalLine { entry, raw })?;
+    line.push(b'\n');
+
+    let mut file = self
+      .file
+      .lock()
+      .map_err(|_| io::Error::new(io::ErrorKind::Other, "journal lock poisoned"))?;
+    file.write_all(&line)?;
+    file.flush()
+  }
+
+  fn query(&self, query: &JournalQuery) -> io::Result<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+
+    for line in BufReader::new(File::open(&self.path)?).lines() {
+      let line = line?;
+      if line.is_empty() {
+        continue;
+      }
+
+      // a line cut short by a crash shouldn't hide the rest of the history
+      let entry = serde_json::from_str::<StoredLine>(&line).ok().and_then(|stored| {
+        let message = RecvMessage::try_from(stored.raw).ok()?;
+        JournalEntry::from_message(stored.time, &message)
+      });
+
+      match entry {
+        Some(entry) if query.matches(&entry) => entries.push(entry),
+        Some(_) => {}
+        None => tracing::warn!(target: "concord4::journal", "skipping unreadable journal line: {}", line),
+      }
+    }
+
+    Ok(entries)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+  use crate::{
+    equipment::{AlarmEventData, ArmingLevel, EventSource},
+    fixtures::{alarm_trouble, arming_level},
+  };
+
+  fn at(secs: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+  }
+
+  fn alarm(partition_number: u8, event: Event) -> RecvMessage {
+    alarm_trouble(partition_number, EventSource::Zone, event)
+  }
+
+  /// one entry a second: an alarm, another partition arming, the alarm restoring, then disarming
+  fn history() -> Vec<JournalEntry> {
+    [
+      alarm(1, Event::Alarm(AlarmEventData::Fire)),
+      arming_level(2, ArmingLevel::Away),
+      alarm(1, Event::AlarmRestoral(AlarmEventData::Fire)),
+      arming_level(1, ArmingLevel::Off),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(secs, message)| JournalEntry::from_message(at(secs as u64), message).expect("message is journaled"))
+    .collect()
+  }
+
+  fn kinds(entries: &[JournalEntry]) -> Vec<(u8, JournalKind)> {
+    entries.iter().map(|entry| (entry.partition, entry.kind)).collect()
+  }
+
+  #[test]
+  fn only_events_and_arming_are_journaled() {
+    assert!(JournalEntry::from_message(at(0), &RecvMessage::EqptListDone).is_none());
+
+    let entry = JournalEntry::from_message(at(0), &arming_level(1, ArmingLevel::Home)).expect("arming is journaled");
+    assert_eq!(entry.kind, JournalKind::ArmingLevel);
+    assert_eq!(entry.user, Some(CodeType::User(1)));
+  }
+
+  #[test]
+  fn memory_journal_drops_the_oldest_entries() {
+    let journal = MemoryJournal::new(3);
+    for entry in history() {
+      journal.append(&entry).expect("could not append");
+    }
+
+    let entries = journal.query(&JournalQuery::new()).expect("could not query");
+    assert_eq!(
+      kinds(&entries),
+      [
+        (2, JournalKind::ArmingLevel),
+        (1, JournalKind::AlarmRestoral),
+        (1, JournalKind::ArmingLevel)
+      ]
+    );
+    assert_eq!(entries[0].time, at(1));
+  }
+
+  #[test]
+  fn query_filters_by_time_partition_and_kind() {
+    let journal = MemoryJournal::new(10);
+    for entry in history() {
+      journal.append(&entry).expect("could not append");
+    }
+    let query = |query: JournalQuery| kinds(&journal.query(&query).expect("could not query"));
+
+    // since is inclusive and until is exclusive
+    assert_eq!(
+      query(JournalQuery::new().since(at(1)).until(at(3))),
+      [(2, JournalKind::ArmingLevel), (1, JournalKind::AlarmRestoral)]
+    );
+    assert_eq!(
+      query(JournalQuery::new().partition(1)),
+      [
+        (1, JournalKind::Alarm),
+        (1, JournalKind::AlarmRestoral),
+        (1, JournalKind::ArmingLevel)
+      ]
+    );
+    assert_eq!(
+      query(
+        JournalQuery::new()
+          .kind(JournalKind::Alarm)
+          .kind(JournalKind::AlarmRestoral)
+      ),
+      [(1, JournalKind::Alarm), (1, JournalKind::AlarmRestoral)]
+    );
+    assert_eq!(
+      query(
+        JournalQuery::new()
+          .partition(1)
+          .kind(JournalKind::ArmingLevel)
+          .since(at(1))
+      ),
+      [(1, JournalKind::ArmingLevel)]
+    );
+    assert!(query(JournalQuery::new().partition(3)).is_empty());
+  }
+
+  #[test]
+  fn recorded_entries_are_written_in_the_background() {
+    let journal = Journal::new(MemoryJournal::new(10));
+    journal.record(&RecvMessage::EqptListDone);
+    journal.record(&arming_level(1, ArmingLevel::Off));
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let entries = loop {
+      let entries = journal.query(&JournalQuery::new()).expect("could not query");
+      if !entries.is_empty() || std::time::Instant::now() > deadline {
+        break entries;
+      }
+
+      std::thread::sleep(Duration::from_millis(1));
+    };
+
+    assert_eq!(kinds(&entries), [(1, JournalKind::ArmingLevel)]);
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn json_lines_journal_survives_reopening() {
+    let path = std::env::temp_dir().join(format!("concord4-journal-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    {
+      let journal = JsonLinesJournal::open(&path).expect("could not open journal");
+      for entry in history() {
+        journal.append(&entry).expect("could not append");
+      }
+    }
+
+    // a line cut short by a crash is skipped rather than ending the history, or swallowing the next entry
+    {
+      let mut file = OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .expect("could not open file");
+      file.write_all(b"{\"time\":").expect("could not write");
+    }
+
+    let journal = JsonLinesJournal::open(&path).expect("could not reopen journal");
+    journal
+      .append(&JournalEntry::from_message(at(4), &arming_level(2, ArmingLevel::Off)).expect("arming is journaled"))
+      .expect("could not append");
+
+    let entries = journal
+      .query(&JournalQuery::new().since(at(2)))
+      .expect("could not query");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+      kinds(&entries),
+      [
+        (1, JournalKind::AlarmRestoral),
+        (1, JournalKind::ArmingLevel),
+        (2, JournalKind::ArmingLevel)
+      ]
+    );
+    assert_eq!(
+      entries.iter().map(|entry| entry.time).collect::<Vec<_>>(),
+      [at(2), at(3), at(4)]
+    );
+    assert!(matches!(
+      &entries[0].message,
+      RecvMessage::AlarmTrouble(alarm) if matches!(alarm.event, Event::AlarmRestoral(AlarmEventData::Fire))
+    ));
+  }
+}
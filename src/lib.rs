@@ -1,32 +1,82 @@
 #![doc = include_str!("../README.md")]
 
+use std::{future::Future, ops::Deref, time::Duration};
+
+use futures::Stream;
+use handle::{bypass_confirmation, confirm, confirmation, panic_confirmation};
+use reconnect::{Connector, Supervisor};
 use serial::Serial;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
+mod builder;
 mod commands;
 mod communication;
 mod consts;
 mod decode;
 mod equipment;
+#[cfg(test)]
+mod fixtures;
+mod handle;
+mod journal;
+mod reconnect;
 mod serial;
+#[cfg(feature = "simulator")]
+mod simulator;
 mod state;
+mod subscription;
 mod touchpad;
+mod transport;
 
-pub use commands::{ArmLevel, ArmMode, ArmOptions, DisarmOptions, Keypress, ListRequest};
+pub use builder::Concord4Builder;
+pub use commands::{
+  ArmLevel, ArmMode, ArmOptions, ConfirmOutcome, DisarmOptions, Keypress, ListRequest, PanicConfirmation, PanicKind,
+};
 pub use communication::{RecvMessage, SendableMessage};
-pub use equipment::{ArmingLevel, PanelData, PartitionData, ZoneData};
-pub use state::{ConcordState as ConcordStateInner, WrappedState as ConcordState};
+pub use equipment::{
+  AlarmEventData, AlarmTrouble, ArmingLevel, ArmingLevelData, BypassEventData, ClosingEventData, CodeType,
+  ConditionChange, ConditionKind, ConditionSource, DelayDirection, DelayExtension, DelayPhase, EntryExitDelay, Event,
+  EventSource, Feature, FeatureState, FireEventData, LightAttach, LightsState, OpeningEventData, PanelData, PanelType,
+  PartitionConfigEventData, PartitionData, PartitionEventData, PartitionTestEventData, SirenSetup, SirenStop,
+  SuperBusDeviceCapability, SuperBusDeviceCapabilityData, SuperBusDeviceData, SuperBusDeviceStatus,
+  SystemConfigChangeEventData, SystemEventData, SystemTroubleEventData, TimeDate, UserData, UserLights, ZoneData,
+  ZoneStatus, ZoneStatusData, ZoneStatusFlags, ZoneType,
+};
+pub use handle::Concord4Handle;
+#[cfg(feature = "json")]
+pub use journal::JsonLinesJournal;
+pub use journal::{Journal, JournalBackend, JournalEntry, JournalKind, JournalQuery, MemoryJournal};
+pub use reconnect::ReconnectPolicy;
+pub use serial::{Concord4Codec, PanelCodec, PendingSend, SendOutcome};
+#[cfg(feature = "simulator")]
+pub use simulator::{Simulator, SimulatorHandle};
+pub use state::{
+  ActiveCondition, ConcordState as ConcordStateInner, DelayCountdown, DelayTick, SirenState, StateChange,
+  WrappedState as ConcordState,
+};
+pub use subscription::{Lagged, Subscription};
+pub use touchpad::TouchpadDisplay;
+pub use transport::Transport;
 
 /// Struct representing a connection to a Concord4 panel.
 /// Contains the current state of the alarm panel and methods to interact with it.
 ///
-/// call `Concord4::open` to create a new connection.
+/// call `Concord4::open` to create a new connection over a serial port, \
+/// or `Concord4::from_transport` to use any other [Transport]. \
+/// `Concord4::builder` tunes timeouts, retries and bootstrapping before connecting.
+///
+/// the commands live on [Concord4Handle] (which [Concord4] derefs to); \
+/// call `Concord4::spawn` to drive the connection on a background task and share handles between tasks. \
+/// without spawning, use the `Concord4::drive_*` versions of the confirmed commands, which drive the connection while they wait.
 pub struct Concord4 {
   /// The current state of the server. The state is Send + Sync, so it can be shared between threads.
   pub state: ConcordState,
 
   // internal
   serial: Serial,
+  handle: Concord4Handle,
+  events: broadcast::Sender<RecvMessage>,
+  changes: broadcast::Sender<StateChange>,
+  supervisor: Supervisor,
 }
 
 impl Concord4 {
@@ -40,29 +90,232 @@ impl Concord4 {
   ///
   /// # example
   /// ```no_run
-  /// let mut client = Concord4::open("/dev/ttyUSB0").await;
+  /// # use concord4::Concord4;
+  /// # async fn example() {
+  /// let mut client = Concord4::open("/dev/ttyUSB0").await.expect("could not open serial port");
+  /// # }
   /// ```
   pub async fn open(path: &str) -> Result<Self, ClientError> {
-    let state = ConcordState::default();
-    let serial = Serial::init(path).await?;
+    Concord4Builder::new().open(path).await
+  }
+
+  /// create a new connection to a Concord4 server over an already opened transport
+  ///
+  /// # args
+  /// `transport`: [Transport] - any [tokio::io::AsyncRead] + [tokio::io::AsyncWrite] stream connected to the panel
+  ///
+  /// # returns
+  /// a new [Concord4] struct
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::Concord4;
+  /// # async fn example() {
+  /// let port = tokio_serial::SerialStream::open(&tokio_serial::new("/dev/ttyUSB0", 9600)).expect("could not open port");
+  /// let mut client = Concord4::from_transport(port);
+  /// # }
+  /// ```
+  pub fn from_transport<T: Transport + 'static>(transport: T) -> Self {
+    Concord4Builder::new().build(transport)
+  }
+
+  /// create a new connection to a Concord4 server using a function that opens the transport
+  ///
+  /// unlike [Concord4::from_transport], the connection can be reopened with [Concord4::with_reconnect]
+  ///
+  /// # args
+  /// `connect`: a function returning a future that opens a [Transport] to the panel
+  ///
+  /// # returns
+  /// a new [Concord4] struct, or a [ClientError] if the first connection failed
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example() {
+  /// let mut client = Concord4::connect_with(|| async {
+  ///   tokio::net::TcpStream::connect("192.168.1.50:4000").await.map_err(ClientError::Transport)
+  /// })
+  /// .await
+  /// .expect("could not connect");
+  /// # }
+  /// ```
+  pub async fn connect_with<F, Fut, T>(connect: F) -> Result<Self, ClientError>
+  where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, ClientError>> + Send + 'static,
+    T: Transport + 'static,
+  {
+    Concord4Builder::new().connect_with(connect).await
+  }
+
+  /// open a new connection to a Concord4 server through a serial-to-ethernet adapter (ser2net, raw tcp socket)
+  ///
+  /// the adapter must pass bytes through untouched; the same framing is used as over a local serial port
+  ///
+  /// # args
+  /// `addr`: [tokio::net::ToSocketAddrs] - the address of the adapter
+  ///
+  /// # returns
+  /// a new [Concord4] struct
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::Concord4;
+  /// # async fn example() {
+  /// let mut client = Concord4::connect_tcp("192.168.1.50:4000").await.expect("could not connect to adapter");
+  /// # }
+  /// ```
+  pub async fn connect_tcp<A: tokio::net::ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+    Concord4Builder::new().connect_tcp(addr).await
+  }
 
-    Ok(Self { state, serial })
+  /// configure a new connection
+  ///
+  /// # returns
+  /// a [Concord4Builder] with the default settings
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example() {
+  /// let mut client = Concord4::builder()
+  ///   .response_timeout(Duration::from_secs(5))
+  ///   .open("/dev/ttyUSB0")
+  ///   .await
+  ///   .expect("could not open serial port");
+  /// # }
+  /// ```
+  pub fn builder() -> Concord4Builder {
+    Concord4Builder::new()
   }
 
-  /// send a raw command to the Concord4 panel
+  /// reopen the transport whenever it closes instead of ending the stream
+  ///
+  /// while disconnected the state is marked stale and [StateChange::Disconnected] is published; \
+  /// once reconnected the active conditions, delays, sirens and lights are cleared (whatever happened meanwhile was missed), \
+  /// [StateChange::Connected] is published and the automation image is requested again; \
+  /// the panel is replaced and zones, partitions and groups it doesn't list again are dropped. \
+  /// only connections made with [Concord4::open], [Concord4::connect_tcp] or [Concord4::connect_with] can reconnect
   ///
   /// # args
-  /// `command`: [SendableMessage] - the command to send
+  /// `policy`: [ReconnectPolicy] - how long to wait between attempts
+  ///
+  /// # returns
+  /// the same [Concord4], now supervised
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example() {
+  /// let client = Concord4::open("/dev/ttyUSB0")
+  ///   .await
+  ///   .expect("could not open serial port")
+  ///   .with_reconnect(ReconnectPolicy::default())
+  ///   .spawn();
+  /// # }
+  /// ```
+  pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+    if self.supervisor.connector.is_none() {
+      tracing::warn!(target: "concord4::reconnect", "connection was made from a transport that can't be reopened, reconnect is disabled");
+    }
+
+    self.supervisor.policy = Some(policy);
+    self
+  }
+
+  /// record alarm/trouble events and arming level changes in a [Journal]
+  ///
+  /// # args
+  /// `backend`: [JournalBackend] - where to keep the entries, e.g. a [MemoryJournal]
+  ///
+  /// # returns
+  /// the same [Concord4], now recording to the journal that [Concord4Handle::journal] returns, \
+  /// including on handles taken before it was attached; only the first journal attached is kept
+  ///
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example() {
+  /// let client = Concord4::open("/dev/ttyUSB0")
+  ///   .await
+  ///   .expect("could not open serial port")
+  ///   .with_journal(MemoryJournal::new(1000))
+  ///   .spawn();
+  /// # }
+  /// ```
+  pub fn with_journal(self, backend: impl JournalBackend) -> Self {
+    self.handle.set_journal(Journal::new(backend));
+    self
+  }
+
+  fn from_serial(serial: Serial, connector: Option<Connector>, event_capacity: usize) -> Self {
+    let state = ConcordState::default();
+    let (events, _) = broadcast::channel(event_capacity);
+    let (changes, _) = broadcast::channel(event_capacity);
+    let handle = Concord4Handle::new(
+      state.clone(),
+      serial.tx.clone(),
+      events.downgrade(),
+      changes.downgrade(),
+    );
+
+    Self {
+      state,
+      serial,
+      handle,
+      events,
+      changes,
+      supervisor: Supervisor::new(connector),
+    }
+  }
+
+  /// get a cloneable handle to this connection
+  ///
+  /// the handle can send commands and subscribe to messages from other tasks, \
+  /// but nothing moves until this [Concord4] is driven with [Concord4::recv] (or use [Concord4::spawn])
+  ///
+  /// # returns
+  /// a [Concord4Handle] sharing this connection's state
+  pub fn handle(&self) -> Concord4Handle {
+    self.handle.clone()
+  }
+
+  /// drive the connection on a background task
+  ///
+  /// the task runs until the connection closes, updating the state and publishing every message to subscribers
   ///
   /// # returns
-  /// an empty [Ok] if the command was sent successfully, or a [ClientError] if there was an error
+  /// a [Concord4Handle] to interact with the panel
   ///
   /// # example
   /// ```no_run
-  /// client.send(SendableMessage::List(ListRequest::AllData)).await.expect("could not send command");
+  /// # use concord4::*;
+  /// # async fn example() {
+  /// let client = Concord4::open("/dev/ttyUSB0").await.expect("could not open serial port").spawn();
+  ///
+  /// let mut messages = client.subscribe();
+  /// tokio::spawn({
+  ///   let client = client.clone();
+  ///   async move { client.toggle_chime(Some(1)).await }
+  /// });
+  /// # }
   /// ```
-  pub async fn send(&mut self, message: SendableMessage) -> Result<(), ClientError> {
-    self.serial.tx.send(message).await.map_err(ClientError::Sender)
+  pub fn spawn(mut self) -> Concord4Handle {
+    let handle = self.handle();
+
+    tokio::spawn(async move {
+      while let Some(message) = self.recv().await {
+        if let Err(err) = message {
+          tracing::error!(target: "concord4::driver", "error receiving message: {:?}", err);
+        }
+      }
+
+      tracing::info!(target: "concord4::driver", "connection closed, stopping driver");
+    });
+
+    handle
   }
 
   /// receive a message from the Concord4 panel
@@ -73,93 +326,218 @@ impl Concord4 {
   /// # returns
   /// an [Option] containing an [Ok] with a [RecvMessage] if a message was received, \
   /// an [Option] containing an [Err] with a [ClientError] if there was an error, \
-  /// or [None] if the serial port was closed
+  /// or [None] if the serial port was closed (and could not be reopened, see [Concord4::with_reconnect])
   ///
   /// # example
   /// ```no_run
+  /// # use concord4::*;
+  /// # async fn example(mut client: Concord4) {
   /// let message = client.recv().await.expect("could not receive message");
+  /// # }
   /// ```
   pub async fn recv(&mut self) -> Option<Result<RecvMessage, ClientError>> {
     use futures::StreamExt;
 
-    let message = self.serial.next().await?;
+    let message = loop {
+      if let Some(message) = self.serial.next().await {
+        break message;
+      }
+
+      if self.supervisor.connected {
+        self.supervisor.connected = false;
+        self.state.set_stale(true);
+        let _ = self.changes.send(StateChange::Disconnected);
+      }
+
+      let transport = self.supervisor.reconnect().await?;
+      self.serial.reconnect(transport);
+      self.state.clear_transient();
+      self.state.begin_resync();
+      self.supervisor.connected = true;
+      let _ = self.changes.send(StateChange::Connected);
+    };
 
     if let Ok(message) = &message {
-      self.state.handle_result(message.clone());
+      let changes = self.state.handle_result(message.clone());
+
+      if let Some(journal) = self.handle.journal() {
+        journal.record(message);
+      }
+
+      // no subscribers is fine
+      let _ = self.events.send(message.clone());
+      for change in changes {
+        let _ = self.changes.send(change);
+      }
     }
 
     Some(message)
   }
 
-  /// arm the alarm
+  /// arm the alarm and wait for the panel to report the partition as armed in the requested mode, \
+  /// driving the connection while waiting
+  ///
+  /// [Concord4Handle::arm_and_confirm] only sees the panel's response while something else drives the connection, \
+  /// so use this one on a [Concord4] that wasn't spawned. \
+  /// messages received while waiting update [Concord4::state] but are not returned from [Concord4::recv]
   ///
   /// # args
-  /// `options`: [ArmOptions] - the options for arming the alarm
+  /// `options`: [ArmOptions] - the options for arming the alarm \
+  /// `timeout`: [Duration] - how long to wait for the arming level to change
   ///
   /// # returns
-  /// an empty [Ok] if the command was sent successfully, or a [ClientError] if there was an error
+  /// a [ConfirmOutcome] describing how the panel reacted, \
+  /// or a [ClientError] if there was an error
   ///
   /// # example
   /// ```no_run
-  /// client.arm(ArmOptions {
-  ///   mode: ArmMode::Stay,
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(mut client: Concord4) {
+  /// let outcome = client.drive_arm_and_confirm(ArmOptions {
+  ///   mode: ArmMode::Away,
   ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
-  ///   level: Some(ArmLevel::Instant),
+  ///   level: None,
   ///   partition: Some(1),
-  /// }).await.expect("could not arm alarm");
+  /// }, Duration::from_secs(10)).await.expect("could not arm alarm");
+  /// # }
   /// ```
-  pub async fn arm(&mut self, options: ArmOptions) -> Result<(), ClientError> {
+  pub async fn drive_arm_and_confirm(
+    &mut self,
+    options: ArmOptions,
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome, ClientError> {
     let partition = options.partition.unwrap_or(1);
-    if let Some(partition_data) = self.state.partitions.get(&partition) {
-      if partition_data.arming_level != ArmingLevel::Off {
-        return Err(ClientError::Armed);
-      }
-    }
+    let level = options.mode.arming_level();
+    let pending = self.handle.arm(options).await?;
 
-    self.send(SendableMessage::Arm(options)).await
+    confirm(self.messages(), pending, timeout, |message| {
+      confirmation(partition, message, level)
+    })
+    .await
   }
 
-  /// disarm the alarm
-  ///
-  /// # args
-  /// `options`: [DisarmOptions] - the options for disarming the alarm
-  ///
-  /// # returns
-  /// an empty [Ok] if the command was sent successfully, or a [ClientError] if there was an error
+  /// disarm the alarm and wait for the panel to report the partition as disarmed, \
+  /// driving the connection while waiting, see [Concord4::drive_arm_and_confirm]
   ///
   /// # example
   /// ```no_run
-  /// client.disarm(DisarmOptions {
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(mut client: Concord4) {
+  /// let outcome = client.drive_disarm_and_confirm(DisarmOptions {
   ///   code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
   ///   partition: Some(1),
-  /// }).await.expect("could not disarm alarm");
+  /// }, Duration::from_secs(10)).await.expect("could not disarm alarm");
+  /// # }
   /// ```
-  pub async fn disarm(&mut self, options: DisarmOptions) -> Result<(), ClientError> {
-    self.send(SendableMessage::Disarm(options)).await
+  pub async fn drive_disarm_and_confirm(
+    &mut self,
+    options: DisarmOptions,
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome, ClientError> {
+    let partition = options.partition.unwrap_or(1);
+    let pending = self.handle.disarm(options).await?;
+
+    confirm(self.messages(), pending, timeout, |message| {
+      confirmation(partition, message, ArmingLevel::Off)
+    })
+    .await
   }
 
-  /// toggle the chime on the alarm
+  /// bypass a zone and wait for the panel to report it as bypassed, \
+  /// driving the connection while waiting, see [Concord4Handle::bypass_zone_and_confirm]
   ///
-  /// # args
-  /// `partition`: [Option<u8>] - the partition to toggle the chime on, or None for partition 1
+  /// messages received while waiting update [Concord4::state] but are not returned from [Concord4::recv]
   ///
-  /// # returns
-  /// an empty [Ok] if the command was sent successfully, or a [ClientError] if there was an error
+  /// # example
+  /// ```no_run
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(mut client: Concord4) {
+  /// let outcome = client
+  ///   .drive_bypass_zone_and_confirm("p1-z5", [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four], Duration::from_secs(5))
+  ///   .await
+  ///   .expect("could not bypass zone");
+  /// # }
+  /// ```
+  pub async fn drive_bypass_zone_and_confirm(
+    &mut self,
+    zone_id: &str,
+    code: [Keypress; 4],
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<ZoneStatusFlags>, ClientError> {
+    let zone = self.handle.bypass_target(zone_id, true)?;
+    let pending = self.handle.send_bypass(&zone, code).await?;
+
+    confirm(self.messages(), pending, timeout, |message| {
+      bypass_confirmation(&zone, message, true)
+    })
+    .await
+  }
+
+  /// unbypass a zone and wait for the panel to report it as no longer bypassed, \
+  /// driving the connection while waiting, see [Concord4::drive_bypass_zone_and_confirm]
+  pub async fn drive_unbypass_zone_and_confirm(
+    &mut self,
+    zone_id: &str,
+    code: [Keypress; 4],
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<ZoneStatusFlags>, ClientError> {
+    let zone = self.handle.bypass_target(zone_id, false)?;
+    let pending = self.handle.send_bypass(&zone, code).await?;
+
+    confirm(self.messages(), pending, timeout, |message| {
+      bypass_confirmation(&zone, message, false)
+    })
+    .await
+  }
+
+  /// raise a panic alarm on a partition and wait for the panel to report it, \
+  /// driving the connection while waiting, see [Concord4Handle::trigger_panic]
+  ///
+  /// messages received while waiting update [Concord4::state] but are not returned from [Concord4::recv]
   ///
   /// # example
   /// ```no_run
-  /// client.toggle_chime(Some(1)).await.expect("could not toggle chime");
+  /// # use concord4::*;
+  /// # use std::time::Duration;
+  /// # async fn example(mut client: Concord4) {
+  /// let outcome = client
+  ///   .drive_trigger_panic(PanicKind::Fire, 1, PanicConfirmation::confirm(PanicKind::Fire), Duration::from_secs(10))
+  ///   .await
+  ///   .expect("could not trigger panic");
+  /// # }
   /// ```
-  pub async fn toggle_chime(&mut self, partition: Option<u8>) -> Result<(), ClientError> {
-    let partition = partition.unwrap_or(1);
+  pub async fn drive_trigger_panic(
+    &mut self,
+    kind: PanicKind,
+    partition: u8,
+    confirmation: PanicConfirmation,
+    timeout: Duration,
+  ) -> Result<ConfirmOutcome<AlarmTrouble>, ClientError> {
+    let pending = self.handle.send_panic(kind, partition, confirmation).await?;
 
-    if let Some(partition_data) = self.state.partitions.get(&partition) {
-      if partition_data.arming_level != ArmingLevel::Off {
-        return Err(ClientError::Armed);
-      }
-    }
+    confirm(self.messages(), pending, timeout, |message| {
+      panic_confirmation(kind, partition, message)
+    })
+    .await
+  }
+
+  /// every message from [Concord4::recv], as a stream
+  fn messages(&mut self) -> impl Stream<Item = Result<RecvMessage, ClientError>> + '_ {
+    futures::stream::unfold(self, |client| async move {
+      let message = client.recv().await?;
+      Some((message, client))
+    })
+  }
+}
+
+impl Deref for Concord4 {
+  type Target = Concord4Handle;
 
-    self.send(SendableMessage::ToggleChime(Some(partition))).await
+  fn deref(&self) -> &Self::Target {
+    &self.handle
   }
 }
 
@@ -169,21 +547,43 @@ pub enum ClientError {
   /// An error denoting the alarm is armed but must be disarmed for this action
   #[error("Alarm is armed; disarm first")]
   Armed,
+  /// An error denoting the zone id is not one the panel has reported
+  #[error("Unknown zone: {0}")]
+  UnknownZone(String),
+  /// An error denoting the zone is already bypassed
+  #[error("Zone {0} is already bypassed")]
+  ZoneBypassed(String),
+  /// An error denoting the zone is not bypassed
+  #[error("Zone {0} is not bypassed")]
+  ZoneNotBypassed(String),
+  /// An error denoting a panic was requested with a confirmation for a different panic
+  #[error("Panic not confirmed: the confirmation is for a different panic")]
+  PanicNotConfirmed,
+  /// An error denoting a light number outside of 1 to 9
+  #[error("Invalid light: {0}")]
+  InvalidLight(u8),
   /// An error returned by the Encoder
   #[error("Encoder error: {0}")]
   Encoder(std::io::Error),
   /// An error returned by the Decoder
+  #[deprecated(
+    note = "frames that can't be decoded are received as RecvMessage::Corrupt, and read errors are reported as ClientError::Transport"
+  )]
   #[error("Decoder error: {0}")]
   Decoder(std::io::Error),
   /// An error returned by the Sender
   #[error("Sender error: {0}")]
   Sender(#[from] mpsc::error::SendError<SendableMessage>),
   /// An error returned by the Receiver
+  #[deprecated(note = "read errors are reported as ClientError::Transport")]
   #[error("Receiver error: {0}")]
   Receiver(std::io::Error),
   /// An error returned by the Serial port
   #[error("Serial port error: {0}")]
   SerialPort(#[from] tokio_serial::Error),
+  /// An error returned while connecting to, configuring or reading from a transport
+  #[error("Transport error: {0}")]
+  Transport(std::io::Error),
   /// A serial port error
   #[error("Serial port closed")]
   SerialPortClosed,
@@ -191,3 +591,38 @@ pub enum ClientError {
   #[error("Unknown error: {0}")]
   Unknown(String),
 }
+
+/// Error type for a frame or message from the panel that could not be decoded
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "json", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub enum DecodeError {
+  /// The message is shorter than its type requires
+  #[error("Short frame: expected at least {expected} bytes, got {actual}")]
+  ShortFrame { expected: usize, actual: usize },
+  /// The frame contains characters that are not ascii hex
+  #[error("Bad hex: {0:?}")]
+  BadHex(Vec<u8>),
+  /// The checksum at the end of the frame doesn't match its contents
+  #[error("Bad checksum: expected {expected:#04x}, got {actual:#04x}")]
+  BadChecksum { expected: u8, actual: u8 },
+  /// The command (and subcommand) are not part of the protocol
+  #[error("Unknown command: {command:#04x} (subcommand {subcommand:?})")]
+  UnknownCommand { command: u8, subcommand: Option<u8> },
+  /// A field holds a value that the protocol doesn't define
+  #[error("Invalid value for {field}: {value:#04x}")]
+  InvalidValue { field: &'static str, value: u8 },
+}
+
+impl DecodeError {
+  /// make sure `data` is long enough to index up to `expected - 1`
+  pub(crate) fn check_len(data: &[u8], expected: usize) -> Result<(), Self> {
+    if data.len() < expected {
+      return Err(DecodeError::ShortFrame {
+        expected,
+        actual: data.len(),
+      });
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+use crate::{transport::BoxedTransport, ClientError};
+
+/// opens a fresh transport to the panel, used for the first connection and every reconnect
+pub(crate) type Connector = Box<dyn Fn() -> BoxFuture<'static, Result<BoxedTransport, ClientError>> + Send + Sync>;
+
+/// How [crate::Concord4] reconnects after the transport is lost.
+///
+/// The delay before each attempt starts at `initial_delay` and doubles after every failure, up to `max_delay`.
+///
+/// # example
+/// ```no_run
+/// # use concord4::*;
+/// # use std::time::Duration;
+/// # async fn example() {
+/// let client = Concord4::open("/dev/ttyUSB0")
+///   .await
+///   .expect("could not open serial port")
+///   .with_reconnect(ReconnectPolicy {
+///     max_delay: Duration::from_secs(30),
+///     ..Default::default()
+///   });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+  /// how long to wait before the first attempt (default: 1s)
+  pub initial_delay: Duration,
+  /// the longest to ever wait between attempts (default: 60s)
+  pub max_delay: Duration,
+  /// give up after this many failed attempts in a row (default: never)
+  pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+  fn default() -> Self {
+    Self {
+      initial_delay: Duration::from_secs(1),
+      max_delay: Duration::from_secs(60),
+      max_attempts: None,
+    }
+  }
+}
+
+impl ReconnectPolicy {
+  fn delay(&self, attempt: u32) -> Duration {
+    self
+      .initial_delay
+      .saturating_mul(2_u32.saturating_pow(attempt))
+      .min(self.max_delay)
+  }
+}
+
+/// keeps track of how to reopen the transport and how often it has failed
+pub(crate) struct Supervisor {
+  pub connector: Option<Connector>,
+  pub policy: Option<ReconnectPolicy>,
+  pub connected: bool,
+  attempts: u32,
+}
+
+impl Supervisor {
+  pub fn new(connector: Option<Connector>) -> Self {
+    Self {
+      connector,
+      policy: None,
+      connected: true,
+      attempts: 0,
+    }
+  }
+
+  /// keep trying to open a new transport until one connects or the policy gives up
+  ///
+  /// the attempt count lives on the supervisor, so dropping this future part way through doesn't reset the backoff
+  pub async fn reconnect(&mut self) -> Option<BoxedTransport> {
+    let (Some(connector), Some(policy)) = (&self.connector, &self.policy) else {
+      return None;
+    };
+
+    loop {
+      if policy.max_attempts.is_some_and(|max| self.attempts >= max) {
+        tracing::error!(target: "concord4::reconnect", "giving up after {} failed attempts", self.attempts);
+        return None;
+      }
+
+      let delay = policy.delay(self.attempts);
+      self.attempts += 1;
+
+      tracing::info!(target: "concord4::reconnect", "reconnecting in {:?} (attempt {})", delay, self.attempts);
+      tokio::time::sleep(delay).await;
+
+      match connector().await {
+        Ok(transport) => {
+          tracing::info!(target: "concord4::reconnect", "reconnected after {} attempts", self.attempts);
+          self.attempts = 0;
+
+          return Some(transport);
+        }
+        Err(err) => tracing::warn!(target: "concord4::reconnect", "failed to reconnect: {:?}", err),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+  };
+
+  use futures::FutureExt;
+  use tokio::time::Instant;
+
+  use super::*;
+
+  fn policy(max_attempts: Option<u32>) -> ReconnectPolicy {
+    ReconnectPolicy {
+      initial_delay: Duration::from_secs(1),
+      max_delay: Duration::from_secs(10),
+      max_attempts,
+    }
+  }
+
+  /// a connector that fails `failures` times before it opens an in-memory pipe, counting every attempt
+  fn connector(failures: u32, attempts: Arc<AtomicU32>) -> Connector {
+    Box::new(move || {
+      let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+
+      async move {
+        if attempt < failures {
+          Err(ClientError::SerialPortClosed)
+        } else {
+          let (client, _) = tokio::io::duplex(64);
+          Ok(Box::new(client) as BoxedTransport)
+        }
+      }
+      .boxed()
+    })
+  }
+
+  #[test]
+  fn delay_doubles_up_to_the_cap() {
+    let delays: Vec<_> = (0..6).map(|attempt| policy(None).delay(attempt).as_secs()).collect();
+    assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+
+    // the doubling saturates rather than overflowing
+    assert_eq!(policy(None).delay(u32::MAX), Duration::from_secs(10));
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn reconnect_backs_off_until_a_connection_opens() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let mut supervisor = Supervisor::new(Some(connector(3, attempts.clone())));
+    supervisor.policy = Some(policy(None));
+
+    let started = Instant::now();
+    assert!(supervisor.reconnect().await.is_some());
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    assert_eq!(started.elapsed(), Duration::from_secs(1 + 2 + 4 + 8));
+
+    // connecting resets the backoff for the next outage
+    let started = Instant::now();
+    assert!(supervisor.reconnect().await.is_some());
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn reconnect_gives_up_after_max_attempts() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let mut supervisor = Supervisor::new(Some(connector(u32::MAX, attempts.clone())));
+    supervisor.policy = Some(policy(Some(3)));
+
+    assert!(supervisor.reconnect().await.is_none());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+  }
+
+  #[tokio::test]
+  async fn reconnect_needs_a_connector_and_a_policy() {
+    let mut supervisor = Supervisor::new(None);
+    supervisor.policy = Some(policy(None));
+    assert!(supervisor.reconnect().await.is_none());
+
+    let attempts = Arc::new(AtomicU32::new(0));
+    let mut supervisor = Supervisor::new(Some(connector(0, attempts.clone())));
+    assert!(supervisor.reconnect().await.is_none());
+    assert_eq!(attempts.load(Ordering::SeqCst), 0);
+  }
+}
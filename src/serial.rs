@@ -1,27 +1,137 @@
 use crate::{
   communication::{RecvMessage, SendableMessage},
-  consts, ArmLevel, ArmMode, ClientError, Keypress, ListRequest,
+  consts::{self, CtrlFlow},
+  transport::BoxedTransport,
+  ArmLevel, ArmMode, ClientError, DecodeError, Keypress, ListRequest,
 };
 use futures::{SinkExt, Stream, StreamExt};
+#[cfg(feature = "json")]
+use serde::Serialize;
 use std::{
   future::Future,
   io,
   pin::Pin,
   str,
   task::{Context, Poll},
-  time::{Duration, Instant},
+  time::Duration,
+};
+use tokio::{
+  sync::{mpsc, oneshot},
+  time::{Instant, Sleep},
 };
-use tokio::sync::mpsc;
-use tokio_serial::{SerialPort, SerialPortBuilderExt, SerialStream};
 use tokio_util::{
   bytes::{Buf, BufMut, BytesMut},
   codec::{Decoder, Encoder, Framed},
 };
 
 const BYTE: usize = consts::ASCII_BYTE_REAL_LEN;
-const TIMEOUT_THRESHOLD: Duration = Duration::from_secs(2);
 
+/// tuning for the send/receive loop, set through [crate::Concord4Builder]
+#[derive(Debug, Clone)]
+pub(crate) struct SerialConfig {
+  pub response_timeout: Duration,
+  pub max_retries: u8,
+  pub auto_bootstrap: bool,
+  pub bootstrap_delay: Duration,
+  pub queue_capacity: usize,
+}
+
+impl Default for SerialConfig {
+  fn default() -> Self {
+    Self {
+      response_timeout: consts::RESPONSE_TIMEOUT,
+      max_retries: consts::MAX_RETRIES,
+      auto_bootstrap: true,
+      bootstrap_delay: consts::BOOTSTRAP_DELAY,
+      queue_capacity: consts::QUEUE_CAPACITY,
+    }
+  }
+}
+
+/// How the panel responded to a message sent with [crate::Concord4::send].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum SendOutcome {
+  /// the panel acknowledged the message
+  Acked,
+  /// the panel rejected the message
+  Nakked,
+  /// the panel never responded, even after the message was resent
+  RetriesExhausted,
+  /// no response arrived before the deadline given to [PendingSend::timeout]
+  Timeout,
+  /// the connection closed before the panel responded
+  Disconnected,
+}
+
+/// A message that has been queued for the panel but not yet answered.
+///
+/// Await it to find out whether the panel ACKed or NAKed the message. \
+/// The serial loop only runs while [crate::Concord4::recv] is being polled, so keep receiving while waiting.
+#[derive(Debug)]
+#[must_use = "dropping a PendingSend does not cancel the message, but its outcome is lost"]
+pub struct PendingSend {
+  rx: oneshot::Receiver<SendOutcome>,
+}
+
+impl PendingSend {
+  /// wait for the panel to respond, giving up after `duration`
+  ///
+  /// # returns
+  /// the [SendOutcome], or [SendOutcome::Timeout] if `duration` elapsed first
+  pub async fn timeout(self, duration: Duration) -> SendOutcome {
+    tokio::time::timeout(duration, self)
+      .await
+      .unwrap_or(SendOutcome::Timeout)
+  }
+}
+
+impl Future for PendingSend {
+  type Output = SendOutcome;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    Pin::new(&mut self.rx)
+      .poll(cx)
+      .map(|outcome| outcome.unwrap_or(SendOutcome::Disconnected))
+  }
+}
+
+/// a message waiting in the send queue, along with whoever wants to know how the panel responded
 #[derive(Debug)]
+pub struct Outgoing {
+  message: SendableMessage,
+  responder: Option<oneshot::Sender<SendOutcome>>,
+}
+
+impl Outgoing {
+  pub fn new(message: SendableMessage) -> (Self, PendingSend) {
+    let (tx, rx) = oneshot::channel();
+
+    (
+      Self {
+        message,
+        responder: Some(tx),
+      },
+      PendingSend { rx },
+    )
+  }
+}
+
+impl From<SendableMessage> for Outgoing {
+  fn from(message: SendableMessage) -> Self {
+    Self {
+      message,
+      responder: None,
+    }
+  }
+}
+
+impl From<mpsc::error::SendError<Outgoing>> for ClientError {
+  fn from(err: mpsc::error::SendError<Outgoing>) -> Self {
+    ClientError::Sender(mpsc::error::SendError(err.0.message))
+  }
+}
+
 pub struct Serial {
   has_errored: bool,
   preparing: bool,
@@ -30,32 +140,26 @@ pub struct Serial {
   sending: bool,
   resend: bool,
   last_send: Instant,
+  // lives on the struct because the loop future is rebuilt on every poll and would drop it otherwise
+  response_timer: Pin<Box<Sleep>>,
   retry_count: u8,
   last_message: Option<SendableMessage>,
+  responder: Option<oneshot::Sender<SendOutcome>>,
+
+  serial: Framed<BoxedTransport, Concord4Codec>,
 
-  serial: Framed<SerialStream, Concord4Codec>,
+  pub tx: mpsc::Sender<Outgoing>,
+  rx: mpsc::Receiver<Outgoing>,
 
-  pub tx: mpsc::Sender<SendableMessage>,
-  rx: mpsc::Receiver<SendableMessage>,
+  config: SerialConfig,
 }
 
 impl Serial {
-  pub async fn init(path: &str) -> Result<Self, ClientError> {
-    let port = tokio_serial::new(path, consts::BAUD_RATE)
-      .data_bits(consts::DATA_BITS)
-      .parity(consts::PARITY)
-      .timeout(Duration::from_millis(10))
-      .open_native_async()?;
-
-    tracing::info!("Receiving data on {} at 9600 baud:", path);
+  pub fn new(transport: BoxedTransport, config: SerialConfig) -> Self {
+    let serial = Concord4Codec.framed(transport);
+    let (tx, rx) = mpsc::channel(config.queue_capacity);
 
-    // must clear buffer because system won't know which message the ACKs are referring to
-    port.clear(tokio_serial::ClearBuffer::All)?;
-
-    let serial = Concord4Codec.framed(port);
-    let (tx, rx) = mpsc::channel(32);
-
-    Ok(Self {
+    Self {
       has_errored: false,
       preparing: false,
       ready: false,
@@ -63,23 +167,32 @@ impl Serial {
       sending: false,
       resend: false,
       last_send: Instant::now(),
+      response_timer: Box::pin(tokio::time::sleep(config.response_timeout)),
       retry_count: 0,
       last_message: None,
+      responder: None,
 
       serial,
 
       tx,
       rx,
-    })
+
+      config,
+    }
   }
 
   async fn serial_loop(&mut self) -> Result<Option<RecvMessage>, ClientError> {
-    if !self.ready && !self.preparing && !self.sending && self.last_send.elapsed() > Duration::from_secs(10) {
-      tracing::info!(target: "concord4::serial::loop", "serial port has been open for 10 seconds and a clear image has not been received, manually sending list request");
-
-      if let Err(err) = self.tx.send(SendableMessage::List(ListRequest::AllData)).await {
+    if self.config.auto_bootstrap
+      && !self.ready
+      && !self.preparing
+      && !self.sending
+      && self.last_send.elapsed() > self.config.bootstrap_delay
+    {
+      tracing::info!(target: "concord4::serial::loop", "serial port has been open for {:?} and a clear image has not been received, manually sending list request", self.config.bootstrap_delay);
+
+      if let Err(err) = self.tx.send(SendableMessage::List(ListRequest::AllData).into()).await {
         tracing::error!(target: "concord4::serial::loop", "failed to send list request: {:?}", err);
-        return Err(ClientError::Sender(err));
+        return Err(err.into());
       }
 
       self.preparing = true;
@@ -98,6 +211,10 @@ impl Serial {
         } else {
           self.resend = false;
           self.last_send = Instant::now();
+          self
+            .response_timer
+            .as_mut()
+            .reset(self.last_send + self.config.response_timeout);
           self.sending = true;
           self.retry_count += 1;
         }
@@ -109,10 +226,16 @@ impl Serial {
       result = self.serial.next() => {
         if let Some(result) = result {
           match result {
-            Ok(RecvMessage::Ack) | Ok(RecvMessage::Nak) => {
-              match result {
-                Ok(RecvMessage::Ack) => tracing::debug!(target: "concord4::serial::loop", "received ACK"),
-                Ok(RecvMessage::Nak) => tracing::warn!(target: "concord4::serial::loop", "received NAK"),
+            Ok(message @ (RecvMessage::Ack | RecvMessage::Nak)) => {
+              let outcome = match message {
+                RecvMessage::Ack => {
+                  tracing::debug!(target: "concord4::serial::loop", "received ACK");
+                  SendOutcome::Acked
+                }
+                RecvMessage::Nak => {
+                  tracing::warn!(target: "concord4::serial::loop", "received NAK");
+                  SendOutcome::Nakked
+                }
                 _ => unreachable!(),
               };
 
@@ -120,19 +243,45 @@ impl Serial {
               self.sending = false;
               self.last_message = None;
               self.retry_count = 0;
+              self.respond(outcome);
 
-              result.map(Some).map_err(ClientError::Decoder)
+              Ok(Some(message))
             }
-            _ => {
+            Err(err) => {
+              // read errors come from the transport itself (e.g. a reset socket), there is no frame to ACK
+              tracing::error!(target: "concord4::serial::loop", "failed to read from transport: {:?}", err);
+
+              Err(ClientError::Transport(err))
+            }
+            Ok(message @ RecvMessage::Corrupt { .. }) => {
+              // a garbled frame is worth another try, but the panel would resend one that doesn't parse forever
+              let garbled = matches!(
+                message,
+                RecvMessage::Corrupt {
+                  reason: DecodeError::BadHex(_) | DecodeError::BadChecksum { .. },
+                  ..
+                }
+              );
+              let reply = if garbled { SendableMessage::Nak } else { SendableMessage::Ack };
+
+              if let Err(err) = self.serial.send(reply).await {
+                tracing::error!(target: "concord4::serial::loop", "failed to respond to corrupt frame: {:?}", err);
+              }
+
+              Ok(Some(message))
+            }
+            Ok(message) => {
               if let Err(err) = self.serial.send(SendableMessage::Ack).await {
                 tracing::error!(target: "concord4::serial::loop", "failed to send ack: {:?}", err);
               }
 
-              if let Ok(RecvMessage::EqptListDone) = result {
+              if let RecvMessage::EqptListDone = message {
                 if !self.ready {
                   tracing::info!(target: "concord4::serial::loop", "panel is ready to go!");
-                  if let Err(err) = self.tx.send(SendableMessage::DynamicDataRefresh).await {
-                    tracing::error!(target: "concord4::serial::loop", "failed to send dynamic data refresh: {:?}", err);
+                  if self.config.auto_bootstrap {
+                    if let Err(err) = self.tx.send(SendableMessage::DynamicDataRefresh.into()).await {
+                      tracing::error!(target: "concord4::serial::loop", "failed to send dynamic data refresh: {:?}", err);
+                    }
                   }
 
                   self.preparing = false;
@@ -140,34 +289,37 @@ impl Serial {
                 }
               }
 
-              if let Ok(RecvMessage::ClearImage(_)) = result {
+              if let RecvMessage::ClearImage(_) = message {
                 tracing::info!(target: "concord4::serial::loop", "panel requested an image reset");
-                self.preparing = true;
 
-                if let Err(err) = self.tx.send(SendableMessage::List(ListRequest::AllData)).await {
-                  tracing::error!(target: "concord4::serial::loop", "failed to send list request: {:?}", err);
-                  return Err(ClientError::Sender(err));
+                if self.config.auto_bootstrap {
+                  self.preparing = true;
+
+                  if let Err(err) = self.tx.send(SendableMessage::List(ListRequest::AllData).into()).await {
+                    tracing::error!(target: "concord4::serial::loop", "failed to send list request: {:?}", err);
+                    return Err(err.into());
+                  }
                 }
               }
 
-              result.map(Some).map_err(ClientError::Decoder)
+              Ok(Some(message))
             },
           }
         } else {
           tracing::error!(target: "concord4::serial::loop", "serial port closed");
-          self.has_errored = true;
+          self.close();
 
           Err(ClientError::SerialPortClosed)
         }
       },
       Some(message) = self.rx.recv(), if {
         // do not receive messages until the panel is ready for them (post ack/nak)
-        if self.sending && self.last_send.elapsed() < TIMEOUT_THRESHOLD {
+        if self.sending && self.last_send.elapsed() < self.config.response_timeout {
           tracing::trace!(target: "concord4::serial::loop", "not ready to send message because waiting for response, waiting...");
 
           false
-        } else if self.sending && self.last_send.elapsed() >= TIMEOUT_THRESHOLD {
-          if self.retry_count < 5 {
+        } else if self.sending && self.last_send.elapsed() >= self.config.response_timeout {
+          if self.retry_count < self.config.max_retries {
             // resend the last message; we don't want to do this instantly though because the ack may be in flight
             // therefore we will mark the message for resend and wait for the next poll of the stream
             self.resend = true;
@@ -175,7 +327,12 @@ impl Serial {
             tracing::error!(target: "concord4::serial::loop", "maximum retries reached, giving up on message");
             self.sending = false;
             self.preparing = false;
+            self.last_message = None;
             self.retry_count = 0;
+
+            if let Some(responder) = self.responder.take() {
+              let _ = responder.send(SendOutcome::RetriesExhausted);
+            }
           }
 
           false
@@ -183,6 +340,8 @@ impl Serial {
           true
         }
       } => {
+        let Outgoing { message, responder } = message;
+
         tracing::debug!(target: "concord4::serial::loop", "sending message: {:?}", message);
         if let Err(err) = self.serial.send(message.clone()).await {
           tracing::error!(target: "concord4::serial::loop", "failed to send message: {:?}", err);
@@ -190,21 +349,78 @@ impl Serial {
           Err(ClientError::Encoder(err))
         } else {
           self.last_send = Instant::now();
+          self.response_timer.as_mut().reset(self.last_send + self.config.response_timeout);
           self.sending = true;
           self.last_message = Some(message);
+          self.responder = responder;
           self.retry_count = 0;
 
           Ok(None)
         }
       },
+      // wake up when the response is overdue so the message gets resent even if the line is quiet
+      _ = &mut self.response_timer, if self.sending => Ok(None),
       else => {
         tracing::error!(target: "concord4::serial::loop", "serial port closed");
 
-        self.has_errored = true;
+        self.close();
         Err(ClientError::SerialPortClosed)
       },
     }
   }
+
+  /// pick up on a new transport after the old one closed, starting over with a fresh automation image
+  pub fn reconnect(&mut self, transport: BoxedTransport) {
+    self.serial = Concord4Codec.framed(transport);
+
+    // anything queued while the panel was unreachable is stale by now
+    self.drain_queue();
+
+    self.has_errored = false;
+    self.preparing = self.config.auto_bootstrap;
+    self.ready = false;
+    self.last_send = Instant::now();
+
+    if self.config.auto_bootstrap {
+      if let Err(err) = self.tx.try_send(SendableMessage::List(ListRequest::AllData).into()) {
+        tracing::error!(target: "concord4::serial::loop", "failed to send list request: {:?}", err);
+      }
+    }
+  }
+
+  fn respond(&mut self, outcome: SendOutcome) {
+    if let Some(responder) = self.responder.take() {
+      // the caller may have stopped waiting, which is fine
+      let _ = responder.send(outcome);
+    }
+  }
+
+  /// the transport is gone (tty unplugged, socket dropped), so anything in flight will never be ACKed or NAKed
+  fn close(&mut self) {
+    if let Some(message) = self.last_message.take() {
+      tracing::warn!(target: "concord4::serial::loop", "transport closed before a response was received for: {:?}", message);
+    }
+
+    self.has_errored = true;
+    self.preparing = false;
+    self.ready = false;
+
+    self.sending = false;
+    self.resend = false;
+    self.retry_count = 0;
+    self.respond(SendOutcome::Disconnected);
+    self.drain_queue();
+  }
+
+  fn drain_queue(&mut self) {
+    while let Ok(Outgoing { message, responder }) = self.rx.try_recv() {
+      tracing::warn!(target: "concord4::serial::loop", "dropping queued message because the transport closed: {:?}", message);
+
+      if let Some(responder) = responder {
+        let _ = responder.send(SendOutcome::Disconnected);
+      }
+    }
+  }
 }
 
 impl Stream for Serial {
@@ -220,111 +436,85 @@ impl Stream for Serial {
 
     match future.poll(cx) {
       Poll::Ready(Ok(Some(result))) => Poll::Ready(Some(Ok(result))),
-      Poll::Ready(Ok(None)) => Poll::Pending,
+      Poll::Ready(Ok(None)) => {
+        // the loop did some work without receiving anything, so run it again to keep the queue moving
+        cx.waker().wake_by_ref();
+        Poll::Pending
+      }
       Poll::Ready(Err(err)) => Poll::Ready(Some(Err(err))),
       Poll::Pending => Poll::Pending,
     }
   }
 }
 
-#[derive(Debug)]
-struct Concord4Codec;
+/// The client side of the protocol: decodes what the panel sends and encodes what a client sends.
+///
+/// [crate::Concord4] uses this internally, but it can be used with [tokio_util::codec::Framed] directly \
+/// for replay tools or to speak to a panel without the rest of the client.
+///
+/// A frame that can't be decoded is yielded as [RecvMessage::Corrupt] and a command it doesn't know as \
+/// [RecvMessage::Unknown], so one bad frame doesn't end the stream. The codec's own error is only used for transport errors.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Concord4Codec;
+
+/// a single unit read off the wire
+enum Frame {
+  /// a bare ACK or NAK
+  Ctrl(CtrlFlow),
+  /// a checksummed message, without its length byte and checksum, along with the frame as received
+  Message { data: Vec<u8>, raw: Vec<u8> },
+  /// a message that was garbled on the wire
+  Invalid { raw: Vec<u8>, reason: DecodeError },
+}
 
 impl Decoder for Concord4Codec {
   type Item = RecvMessage;
   type Error = io::Error;
 
   fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-    if src.is_empty() {
-      return Ok(None);
-    }
-
-    tracing::trace!(target: "concord4::serial::decoder", "src: {:?}", src);
-
-    let ctrl_ctr = src.as_ref().iter().position(|b| *b == consts::ACK || *b == consts::NAK);
-    let newline = src.as_ref().iter().position(|b| *b == b'\n');
-
-    if let Some(ctrl_pos) = ctrl_ctr {
-      tracing::trace!(target: "concord4::serial::decoder","ctrl_ctr: {:?}", ctrl_ctr);
-      // if there is a linefeed before the control character, we need to process that first
-      if newline.is_some_and(|lf_pos| ctrl_pos < lf_pos) {
-        let ctrl = *src.get(ctrl_pos).expect("infallible");
-
-        let mut after_ctrl = src.split_off(ctrl_pos);
-        after_ctrl.advance(1);
-        src.extend(after_ctrl);
-
-        if ctrl == consts::ACK {
-          tracing::trace!(target: "concord4::serial::decoder","recv: ACK");
-          return Ok(Some(RecvMessage::Ack));
-        } else {
-          tracing::warn!(target: "concord4::serial::decoder","recv: NAK");
-          return Ok(Some(RecvMessage::Nak));
-        }
+    match decode_frame(src)? {
+      Some(Frame::Ctrl(CtrlFlow::Ack)) => {
+        tracing::trace!(target: "concord4::serial::decoder","recv: ACK");
+        Ok(Some(RecvMessage::Ack))
       }
-    }
-
-    if let Some(lf_pos) = newline {
-      // post_lf stands for position after line feed
-      let post_lf = lf_pos + 1;
-
-      // byte here stands for the byte denoting length of the message
-      if src.len() < post_lf + BYTE {
-        tracing::trace!(target: "concord4::serial::decoder","message not finished - waiting for more data");
-        return Ok(None);
+      Some(Frame::Ctrl(CtrlFlow::Nak)) => {
+        tracing::warn!(target: "concord4::serial::decoder","recv: NAK");
+        Ok(Some(RecvMessage::Nak))
       }
+      Some(Frame::Message { data, raw }) => match RecvMessage::try_from(data) {
+        Ok(message) => {
+          if let RecvMessage::Unknown {
+            command, subcommand, ..
+          } = &message
+          {
+            tracing::warn!(target: "concord4::serial::decoder","unknown command {:#04x} (subcommand {:?})", command, subcommand);
+          }
 
-      // byte here stands for the byte denoting length of the message since length doesn't include itself
-      let (data_len, data_len_in_buffer) = if let Some(len_bytes) = src.get((post_lf)..(post_lf + BYTE)) {
-        let data_len = ascii_hex_to_u8(len_bytes);
-        // byte here stands for how each message in buffer is twice as long as the actual data
-        let data_len_in_buffer = data_len as usize * BYTE;
-
-        tracing::trace!(target: "concord4::serial::decoder","data_len: {} hex bytes; {} real bytes;", data_len, data_len_in_buffer);
-
-        (data_len, data_len_in_buffer)
-      } else {
-        (0, 0)
-      };
-
-      // byte here stands for the byte denoting length of the message
-      if data_len_in_buffer == 0 || src.len() < post_lf + BYTE + data_len_in_buffer {
-        tracing::trace!(target: "concord4::serial::decoder","message not finished - waiting for more data");
-        return Ok(None);
-      }
+          Ok(Some(message))
+        }
+        Err(reason) => {
+          tracing::error!(target: "concord4::serial::decoder","failed to parse message: {}", reason);
+          Ok(Some(RecvMessage::Corrupt { raw, reason }))
+        }
+      },
+      Some(Frame::Invalid { raw, reason }) => Ok(Some(RecvMessage::Corrupt { raw, reason })),
+      None => Ok(None),
+    }
+  }
 
-      src.advance(post_lf);
-      // byte here stands for the byte denoting length of the message since length doesn't include itself
-      let full_data = src.split_to(BYTE + data_len_in_buffer);
-      tracing::debug!(target: "concord4::serial::decoder","message received - {:?}", full_data);
-
-      // byte here stands for the byte denoting length of the message
-      let mut data = match ascii_hex_to_bin(full_data.get(BYTE..).expect("infallible")) {
-        Ok(data) => data,
-        Err(_) => return Ok(None),
-      };
-      let checksum = data.pop().expect("infallible");
-
-      tracing::trace!(target: "concord4::serial::decoder","data: {:?}", data);
-      tracing::trace!(target: "concord4::serial::decoder","checksum: {:?}", checksum);
-
-      if validate_checksum(data_len, &data, checksum) {
-        tracing::trace!(target: "concord4::serial::decoder","checksum valid");
-      } else {
-        tracing::error!(target: "concord4::serial::decoder","invalid checksum");
-
-        return Ok(None);
-      }
+  fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    match self.decode(buf)? {
+      Some(frame) => Ok(Some(frame)),
+      None => {
+        // a dropped connection can leave half a frame behind; that is not worth an error
+        if !buf.is_empty() {
+          tracing::warn!(target: "concord4::serial::decoder", "discarding incomplete message at end of stream: {:?}", buf);
+          buf.clear();
+        }
 
-      let data = RecvMessage::try_from(data);
-      if let Ok(data) = data {
-        return Ok(Some(data));
-      } else {
-        tracing::error!(target: "concord4::serial::decoder","failed to parse message");
+        Ok(None)
       }
     }
-
-    Ok(None)
   }
 }
 
@@ -332,7 +522,6 @@ impl Encoder<SendableMessage> for Concord4Codec {
   type Error = io::Error;
 
   fn encode(&mut self, item: SendableMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
-    use tokio_util::bytes::BufMut;
     tracing::trace!(target: "concord4::serial::encoder","sending: {:?}", item);
     tracing::trace!(target: "concord4::serial::encoder","dst before: {:?}", dst);
 
@@ -355,7 +544,7 @@ impl Encoder<SendableMessage> for Concord4Codec {
           data.push(0x2);
         }
         other => {
-          data.put_slice(&[0x2, other as u8]);
+          data.put_slice(&[0x2, other.into()]);
         }
       },
       SendableMessage::Arm(options) => {
@@ -395,22 +584,221 @@ impl Encoder<SendableMessage> for Concord4Codec {
       }
     }
 
-    // the length of the message doesn't include the length byte itself
-    data[0] = data.len() as u8;
-    data.push(compute_checksum(&data));
-    let msg = ascii_hex_to_string(data.as_ref());
-    let msg_with_lf = ["\n", &msg].concat();
+    encode_frame(data, dst);
 
-    tracing::debug!(target: "concord4::serial::encoder","sending message: {:?}", msg_with_lf);
-    tracing::trace!(target: "concord4::serial::encoder","sending message bytes: {:?}", msg_with_lf.as_bytes());
+    Ok(())
+  }
+}
 
-    dst.put(msg_with_lf.as_bytes());
-    tracing::trace!(target: "concord4::serial::encoder","dst after: {:?}", dst);
+/// The panel side of the protocol: decodes what a client sends and encodes what a panel sends.
+///
+/// This is the inverse of [Concord4Codec], useful for simulators, replay tools and round-trip tests.
+///
+/// # example
+/// ```
+/// # use concord4::{ArmingLevel, ArmingLevelData, CodeType, PanelCodec, RecvMessage};
+/// use tokio_util::{bytes::BytesMut, codec::Encoder};
+///
+/// let mut frame = BytesMut::new();
+/// let message = RecvMessage::ArmingLevel(ArmingLevelData {
+///   partition_number: 1,
+///   area_number: 0,
+///   user: CodeType::User(0),
+///   arming_level: ArmingLevel::Away,
+/// });
+///
+/// PanelCodec.encode(message, &mut frame).expect("could not encode message");
+/// assert_eq!(&frame[..], b"\n08220101000000032F");
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PanelCodec;
+
+impl Decoder for PanelCodec {
+  type Item = Result<SendableMessage, DecodeError>;
+  type Error = io::Error;
+
+  fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    match decode_frame(src)? {
+      Some(Frame::Ctrl(CtrlFlow::Ack)) => Ok(Some(Ok(SendableMessage::Ack))),
+      Some(Frame::Ctrl(CtrlFlow::Nak)) => Ok(Some(Ok(SendableMessage::Nak))),
+      Some(Frame::Message { data, .. }) => {
+        let message = SendableMessage::try_from(data);
+        if let Err(err) = &message {
+          tracing::error!(target: "concord4::serial::decoder","failed to parse message: {}", err);
+        }
+
+        Ok(Some(message))
+      }
+      Some(Frame::Invalid { reason, .. }) => Ok(Some(Err(reason))),
+      None => Ok(None),
+    }
+  }
+}
+
+impl Encoder<RecvMessage> for PanelCodec {
+  type Error = io::Error;
+
+  fn encode(&mut self, item: RecvMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+    tracing::trace!(target: "concord4::serial::encoder","sending: {:?}", item);
+
+    match item {
+      RecvMessage::Ack => dst.put_u8(consts::ACK),
+      RecvMessage::Nak => dst.put_u8(consts::NAK),
+      // handy for simulating a noisy line
+      RecvMessage::Corrupt { raw, .. } => {
+        dst.put_u8(b'\n');
+        dst.put_slice(&raw);
+      }
+      other => {
+        let message = Vec::<u8>::try_from(other)
+          .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message has no frame form"))?;
+
+        // first byte is length so zero for now
+        let mut data: Vec<u8> = vec![0x0];
+        data.extend(message);
+
+        encode_frame(data, dst);
+      }
+    }
 
     Ok(())
   }
 }
 
+/// decodes the next unit off the wire: either a bare control character or a checksummed message
+fn decode_frame(src: &mut BytesMut) -> Result<Option<Frame>, io::Error> {
+  if src.is_empty() {
+    return Ok(None);
+  }
+
+  tracing::trace!(target: "concord4::serial::decoder", "src: {:?}", src);
+
+  let ctrl_ctr = src.as_ref().iter().position(|b| *b == consts::ACK || *b == consts::NAK);
+  let newline = src.as_ref().iter().position(|b| *b == b'\n');
+
+  if let Some(ctrl_pos) = ctrl_ctr {
+    tracing::trace!(target: "concord4::serial::decoder","ctrl_ctr: {:?}", ctrl_ctr);
+    // a control character ahead of any frame (or on its own, which is how an ACK usually arrives) goes first; \
+    // one after a line feed has to wait for that frame to be processed
+    if newline.map_or(true, |lf_pos| ctrl_pos < lf_pos) {
+      let mut after_ctrl = src.split_off(ctrl_pos);
+      let ctrl = after_ctrl.get_u8();
+      src.extend(after_ctrl);
+
+      // the position search only matches control characters
+      if let Ok(ctrl) = CtrlFlow::try_from(ctrl) {
+        return Ok(Some(Frame::Ctrl(ctrl)));
+      }
+    }
+  }
+
+  if let Some(lf_pos) = newline {
+    // post_lf stands for position after line feed
+    let post_lf = lf_pos + 1;
+
+    // byte here stands for the byte denoting length of the message
+    if src.len() < post_lf + BYTE {
+      tracing::trace!(target: "concord4::serial::decoder","message not finished - waiting for more data");
+      return Ok(None);
+    }
+
+    // byte here stands for the byte denoting length of the message since length doesn't include itself
+    let len_bytes = &src[post_lf..post_lf + BYTE];
+    let data_len = match ascii_hex_to_u8(len_bytes) {
+      Ok(data_len) => data_len,
+      Err(reason) => {
+        // without a length there is no telling where the frame ends, so skip to the next line feed
+        tracing::error!(target: "concord4::serial::decoder","invalid length: {}", reason);
+        let raw = len_bytes.to_vec();
+        src.advance(post_lf);
+
+        return Ok(Some(Frame::Invalid { raw, reason }));
+      }
+    };
+    // byte here stands for how each message in buffer is twice as long as the actual data
+    let data_len_in_buffer = data_len as usize * BYTE;
+
+    tracing::trace!(target: "concord4::serial::decoder","data_len: {} hex bytes; {} real bytes;", data_len, data_len_in_buffer);
+
+    // the length covers at least the checksum, so a zero length would never finish
+    if data_len == 0 {
+      src.advance(post_lf);
+      let raw = src.split_to(BYTE).to_vec();
+
+      return Ok(Some(Frame::Invalid {
+        raw,
+        reason: DecodeError::ShortFrame { expected: 1, actual: 0 },
+      }));
+    }
+
+    // byte here stands for the byte denoting length of the message
+    if src.len() < post_lf + BYTE + data_len_in_buffer {
+      tracing::trace!(target: "concord4::serial::decoder","message not finished - waiting for more data");
+      return Ok(None);
+    }
+
+    src.advance(post_lf);
+    // byte here stands for the byte denoting length of the message since length doesn't include itself
+    let full_data = src.split_to(BYTE + data_len_in_buffer);
+    tracing::debug!(target: "concord4::serial::decoder","message received - {:?}", full_data);
+
+    // byte here stands for the byte denoting length of the message
+    let mut data = match ascii_hex_to_bin(&full_data[BYTE..]) {
+      Ok(data) => data,
+      Err(reason) => {
+        tracing::error!(target: "concord4::serial::decoder","invalid message: {}", reason);
+
+        return Ok(Some(Frame::Invalid {
+          raw: full_data.to_vec(),
+          reason,
+        }));
+      }
+    };
+    // the length was checked to be non-zero above, so there is always a checksum
+    let checksum = data.pop().unwrap_or_default();
+
+    tracing::trace!(target: "concord4::serial::decoder","data: {:?}", data);
+    tracing::trace!(target: "concord4::serial::decoder","checksum: {:?}", checksum);
+
+    let expected = compute_checksum([&[data_len], data.as_slice()].concat().as_ref());
+    if expected == checksum {
+      tracing::trace!(target: "concord4::serial::decoder","checksum valid");
+    } else {
+      tracing::error!(target: "concord4::serial::decoder","invalid checksum");
+
+      return Ok(Some(Frame::Invalid {
+        raw: full_data.to_vec(),
+        reason: DecodeError::BadChecksum {
+          expected,
+          actual: checksum,
+        },
+      }));
+    }
+
+    return Ok(Some(Frame::Message {
+      data,
+      raw: full_data.to_vec(),
+    }));
+  }
+
+  Ok(None)
+}
+
+/// `data` must start with a placeholder for the length byte
+fn encode_frame(mut data: Vec<u8>, dst: &mut BytesMut) {
+  // the length of the message doesn't include the length byte itself
+  data[0] = data.len() as u8;
+  data.push(compute_checksum(&data));
+  let msg = ascii_hex_to_string(data.as_ref());
+  let msg_with_lf = ["\n", &msg].concat();
+
+  tracing::debug!(target: "concord4::serial::encoder","sending message: {:?}", msg_with_lf);
+  tracing::trace!(target: "concord4::serial::encoder","sending message bytes: {:?}", msg_with_lf.as_bytes());
+
+  dst.put(msg_with_lf.as_bytes());
+  tracing::trace!(target: "concord4::serial::encoder","dst after: {:?}", dst);
+}
+
 fn handle_keypress(data: &mut Vec<u8>, partition: u8, keys: &[Keypress]) {
   data.put_slice(&[0x40, partition, 0x0]);
   data.extend(keys.iter().copied().map(Into::<u8>::into));
@@ -420,12 +808,6 @@ fn compute_checksum(msg: &[u8]) -> u8 {
   (msg.iter().map(|b| *b as usize).sum::<usize>() % 256) as u8
 }
 
-fn validate_checksum(first_byte: u8, msg: &[u8], checksum: u8) -> bool {
-  let calc_sum = compute_checksum([&[first_byte], msg].concat().as_ref());
-
-  checksum == calc_sum
-}
-
 fn ascii_hex_to_string(hex: &[u8]) -> String {
   use std::fmt::Write;
   hex.iter().fold(String::new(), |mut output, b| {
@@ -434,15 +816,495 @@ fn ascii_hex_to_string(hex: &[u8]) -> String {
   })
 }
 
-fn ascii_hex_to_u8(hex: &[u8]) -> u8 {
-  u8::from_str_radix(str::from_utf8(hex).expect("not valid ascii??"), 16).expect("not valid hex??")
+fn ascii_hex_to_u8(hex: &[u8]) -> Result<u8, DecodeError> {
+  str::from_utf8(hex)
+    .ok()
+    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+    .ok_or_else(|| DecodeError::BadHex(hex.to_vec()))
+}
+
+fn ascii_hex_to_bin(hex: &[u8]) -> Result<Vec<u8>, DecodeError> {
+  hex.chunks_exact(2).map(ascii_hex_to_u8).collect()
 }
 
-fn ascii_hex_to_bin(hex: &[u8]) -> Result<Vec<u8>, std::num::ParseIntError> {
-  hex
-    .chunks_exact(2)
-    .map(str::from_utf8)
-    .filter_map(Result::ok)
-    .map(|hex| u8::from_str_radix(hex, 16))
-    .collect()
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashSet, mem};
+
+  use super::*;
+  use crate::{decode, equipment::*, touchpad::TouchpadDisplay, ArmOptions, DisarmOptions};
+
+  fn decode_all<D: Decoder>(codec: &mut D, bytes: &[u8]) -> Vec<D::Item>
+  where
+    D::Error: std::fmt::Debug,
+  {
+    let mut src = BytesMut::from(bytes);
+    let mut items = Vec::new();
+
+    while let Some(item) = codec.decode(&mut src).expect("decoding never fails outright") {
+      items.push(item);
+    }
+
+    items
+  }
+
+  #[test]
+  fn lone_ack_is_decoded() {
+    let items = decode_all(&mut Concord4Codec, &[consts::ACK]);
+
+    assert!(matches!(items.as_slice(), [RecvMessage::Ack]), "{:?}", items);
+  }
+
+  #[test]
+  fn lone_nak_is_decoded() {
+    let items = decode_all(&mut Concord4Codec, &[consts::NAK]);
+
+    assert!(matches!(items.as_slice(), [RecvMessage::Nak]), "{:?}", items);
+  }
+
+  fn encode<E: Encoder<T>, T>(codec: &mut E, item: T) -> BytesMut
+  where
+    E::Error: std::fmt::Debug,
+  {
+    let mut dst = BytesMut::new();
+    codec.encode(item, &mut dst).expect("could not encode");
+
+    dst
+  }
+
+  /// one of every message a panel can send
+  fn panel_messages() -> Vec<RecvMessage> {
+    vec![
+      RecvMessage::Ack,
+      RecvMessage::Nak,
+      RecvMessage::PanelType(PanelData {
+        panel_type: PanelType::Concord,
+        hardware_revision: "J0".to_string(),
+        software_revision: "612".to_string(),
+        serial_number: "12A1B2C3".to_string(),
+      }),
+      RecvMessage::AutomationEventLost(vec![]),
+      RecvMessage::ZoneData(ZoneData {
+        partition_number: 1,
+        area_number: 0,
+        group_number: 10,
+        zone_number: 258,
+        zone_type: ZoneType::RF,
+        zone_status: ZoneStatusFlags {
+          faulted: true,
+          bypassed: true,
+          ..Default::default()
+        },
+        zone_text: "FRONT DOOR".to_string(),
+      }),
+      RecvMessage::PartitionData(PartitionData {
+        partition_number: 1,
+        area_number: 0,
+        arming_level: ArmingLevel::Away,
+        zones: HashSet::new(),
+        features: HashSet::new(),
+        last_armed_by: None,
+      }),
+      RecvMessage::SuperBusDevData(SuperBusDeviceData {
+        partition_number: 1,
+        area_number: 0,
+        device_id: (0x12, 0x34, 0x56),
+        device_status: SuperBusDeviceStatus::Failed,
+      }),
+      RecvMessage::SuperBusDevCap(SuperBusDeviceCapability {
+        device_id: (0x12, 0x34, 0x56),
+        capability: SuperBusDeviceCapabilityData::InputZones(8),
+      }),
+      RecvMessage::OutputData(vec![0x00, 0x01, 0x02]),
+      RecvMessage::EqptListDone,
+      RecvMessage::UserData(UserData {
+        number: (0x00, 0x01),
+        user_type: CodeType::User(1),
+        code: Some((1, 2, 3, 4)),
+      }),
+      RecvMessage::SchedData(vec![0x01, 0x02]),
+      RecvMessage::SchedEventData(vec![0x03, 0x04]),
+      RecvMessage::LightAttach(LightAttach {
+        partition_number: 1,
+        area_number: 0,
+        light_number: 3,
+        zone_number: 258,
+      }),
+      RecvMessage::ClearImage(vec![0x00]),
+      RecvMessage::ZoneStatus(ZoneStatusData {
+        partition_number: 1,
+        area_number: 0,
+        zone_number: 5,
+        zone_status: ZoneStatusFlags {
+          tripped: true,
+          alarm: true,
+          ..Default::default()
+        },
+      }),
+      RecvMessage::ArmingLevel(ArmingLevelData {
+        partition_number: 1,
+        area_number: 0,
+        user: CodeType::Master(2),
+        arming_level: ArmingLevel::Home,
+      }),
+      RecvMessage::ArmingLevel(ArmingLevelData {
+        partition_number: 1,
+        area_number: 0,
+        user: CodeType::Other(0x0101),
+        arming_level: ArmingLevel::Off,
+      }),
+      RecvMessage::AlarmTrouble(AlarmTrouble {
+        partition_number: 1,
+        area_number: 0,
+        source_type: EventSource::Zone,
+        source_number: (0, 0, 5),
+        event: Event::Alarm(AlarmEventData::Fire),
+      }),
+      RecvMessage::AlarmTrouble(AlarmTrouble {
+        partition_number: 1,
+        area_number: 0,
+        source_type: EventSource::Zone,
+        source_number: (0, 0, 5),
+        event: Event::AlarmRestoral(AlarmEventData::Fire),
+      }),
+      RecvMessage::AlarmTrouble(AlarmTrouble {
+        partition_number: 1,
+        area_number: 0,
+        source_type: EventSource::System,
+        source_number: (0, 0, 0),
+        event: Event::Unknown(0x42),
+      }),
+      RecvMessage::EntryExitDelay(EntryExitDelay {
+        partition_number: 1,
+        area_number: 0,
+        direction: DelayDirection::Exit,
+        extension: DelayExtension::Extended,
+        phase: DelayPhase::Start,
+        seconds: 300,
+      }),
+      RecvMessage::SirenSetup(SirenSetup {
+        partition_number: 1,
+        area_number: 0,
+        repetitions: 0,
+        cadence: 0xF0F0_F0F0,
+      }),
+      RecvMessage::SirenSync,
+      RecvMessage::SirenGo,
+      RecvMessage::Touchpad(TouchpadDisplay {
+        partition_number: 1,
+        area_number: 0,
+        message_type: 0,
+        display_tokens: decode::encode_text_tokens("INVALID CODE"),
+        text: "INVALID CODE ".to_string(),
+      }),
+      RecvMessage::SirenStop(SirenStop {
+        partition_number: 1,
+        area_number: 0,
+      }),
+      RecvMessage::FeatState(FeatureState {
+        partition_number: 1,
+        area_number: 0,
+        features: [Feature::Chime, Feature::QuickArm].into_iter().collect(),
+      }),
+      RecvMessage::Temp(vec![0x01, 0x00, 0x48]),
+      RecvMessage::TimeAndDate(TimeDate {
+        hour: 13,
+        minute: 37,
+        month: 10,
+        day: 16,
+        year: 26,
+      }),
+      RecvMessage::LightsState(LightsState {
+        partition_number: 1,
+        area_number: 0,
+        lights: [1, 5, 9].into_iter().collect(),
+      }),
+      RecvMessage::UserLights(UserLights {
+        partition_number: 1,
+        area_number: 0,
+        light_number: 0,
+        on: true,
+      }),
+      RecvMessage::Keyfob(vec![0x01, 0x00, 0x03]),
+      RecvMessage::Unknown {
+        command: 0x22,
+        subcommand: Some(0x0f),
+        payload: vec![0x01, 0x02],
+      },
+      RecvMessage::Unknown {
+        command: 0x30,
+        subcommand: None,
+        payload: vec![0x01, 0x02],
+      },
+      RecvMessage::Corrupt {
+        raw: b"0522010100FF".to_vec(),
+        reason: DecodeError::BadChecksum {
+          expected: 0x29,
+          actual: 0xFF,
+        },
+      },
+    ]
+  }
+
+  /// one of every message a client can send
+  fn client_messages() -> Vec<SendableMessage> {
+    let code = [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four];
+
+    vec![
+      SendableMessage::Ack,
+      SendableMessage::Nak,
+      SendableMessage::List(ListRequest::AllData),
+      SendableMessage::List(ListRequest::ZoneData),
+      SendableMessage::List(ListRequest::LightAttach),
+      SendableMessage::Keypress(2, vec![Keypress::LightsOn, Keypress::Three]),
+      SendableMessage::Arm(ArmOptions {
+        mode: ArmMode::Away,
+        code,
+        level: Some(ArmLevel::Silent),
+        partition: Some(1),
+      }),
+      SendableMessage::Disarm(DisarmOptions { code, partition: None }),
+      SendableMessage::ToggleChime(Some(3)),
+      SendableMessage::DynamicDataRefresh,
+    ]
+  }
+
+  #[test]
+  fn panel_messages_round_trip() {
+    for message in panel_messages() {
+      let frame = encode(&mut PanelCodec, message.clone());
+      let decoded = decode_all(&mut Concord4Codec, &frame);
+
+      assert_eq!(decoded.len(), 1, "{:?} decoded as {:?}", message, decoded);
+      assert_eq!(
+        mem::discriminant(&decoded[0]),
+        mem::discriminant(&message),
+        "{:?} decoded as {:?}",
+        message,
+        decoded[0]
+      );
+      assert_eq!(
+        encode(&mut PanelCodec, decoded[0].clone()),
+        frame,
+        "{:?} re-encoded differently",
+        message
+      );
+    }
+  }
+
+  #[test]
+  fn client_messages_round_trip() {
+    for message in client_messages() {
+      let frame = encode(&mut Concord4Codec, message.clone());
+      let decoded = decode_all(&mut PanelCodec, &frame);
+
+      assert_eq!(decoded.len(), 1, "{:?} decoded as {:?}", message, decoded);
+      let decoded = decoded[0].clone().expect("could not decode client message");
+
+      assert_eq!(
+        encode(&mut Concord4Codec, decoded.clone()),
+        frame,
+        "{:?} decoded as {:?}",
+        message,
+        decoded
+      );
+    }
+  }
+
+  #[test]
+  fn arm_decodes_as_its_keypresses() {
+    let frame = encode(
+      &mut Concord4Codec,
+      SendableMessage::Arm(ArmOptions {
+        mode: ArmMode::Stay,
+        code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+        level: Some(ArmLevel::Instant),
+        partition: Some(2),
+      }),
+    );
+    let decoded = decode_all(&mut PanelCodec, &frame);
+
+    let [Ok(SendableMessage::Keypress(2, keys))] = decoded.as_slice() else {
+      panic!("arm decoded as {:?}", decoded);
+    };
+    let keys: Vec<u8> = keys.iter().copied().map(u8::from).collect();
+    assert_eq!(keys, [0x02, 0x01, 0x02, 0x03, 0x04, 0x04]);
+  }
+
+  #[test]
+  fn ctrl_ahead_of_a_frame_comes_first() {
+    for (ctrl, expected) in [(consts::ACK, RecvMessage::Ack), (consts::NAK, RecvMessage::Nak)] {
+      let mut bytes = vec![ctrl];
+      bytes.extend_from_slice(&encode(&mut PanelCodec, RecvMessage::EqptListDone));
+
+      let decoded = decode_all(&mut Concord4Codec, &bytes);
+      assert!(
+        matches!(
+          decoded.as_slice(),
+          [first, RecvMessage::EqptListDone] if mem::discriminant(first) == mem::discriminant(&expected)
+        ),
+        "{:?}",
+        decoded
+      );
+    }
+  }
+
+  #[test]
+  fn ctrl_behind_a_frame_waits_for_it() {
+    let mut bytes = encode(&mut PanelCodec, RecvMessage::EqptListDone).to_vec();
+    bytes.push(consts::ACK);
+
+    let decoded = decode_all(&mut Concord4Codec, &bytes);
+    assert!(
+      matches!(decoded.as_slice(), [RecvMessage::EqptListDone, RecvMessage::Ack]),
+      "{:?}",
+      decoded
+    );
+  }
+
+  #[test]
+  fn partial_frame_waits_for_the_rest() {
+    let frame = encode(&mut PanelCodec, RecvMessage::EqptListDone);
+    let mut src = BytesMut::from(&frame[..frame.len() - 1]);
+
+    assert!(Concord4Codec
+      .decode(&mut src)
+      .expect("decoding never fails outright")
+      .is_none());
+
+    src.extend_from_slice(&frame[frame.len() - 1..]);
+    let decoded = Concord4Codec.decode(&mut src).expect("decoding never fails outright");
+    assert!(matches!(decoded, Some(RecvMessage::EqptListDone)), "{:?}", decoded);
+  }
+
+  #[test]
+  fn bad_checksum_is_corrupt() {
+    // ARM LEVEL for partition 1 with the checksum zeroed
+    let decoded = decode_all(&mut Concord4Codec, b"\n082201010000000300");
+
+    let [RecvMessage::Corrupt { raw, reason }] = decoded.as_slice() else {
+      panic!("decoded as {:?}", decoded);
+    };
+    assert_eq!(raw, b"082201010000000300");
+    assert!(
+      matches!(reason, DecodeError::BadChecksum { actual: 0x00, .. }),
+      "{:?}",
+      reason
+    );
+  }
+
+  #[test]
+  fn bad_length_is_corrupt() {
+    let decoded = decode_all(&mut Concord4Codec, b"\nZZ");
+    assert!(
+      matches!(
+        decoded.as_slice(),
+        [RecvMessage::Corrupt {
+          reason: DecodeError::BadHex(_),
+          ..
+        }]
+      ),
+      "{:?}",
+      decoded
+    );
+
+    let decoded = decode_all(&mut Concord4Codec, b"\n00");
+    assert!(
+      matches!(
+        decoded.as_slice(),
+        [RecvMessage::Corrupt {
+          reason: DecodeError::ShortFrame { .. },
+          ..
+        }]
+      ),
+      "{:?}",
+      decoded
+    );
+  }
+
+  #[test]
+  fn frame_after_a_bad_length_still_decodes() {
+    let mut bytes = b"\nZZ".to_vec();
+    bytes.extend_from_slice(&encode(&mut PanelCodec, RecvMessage::EqptListDone));
+
+    let decoded = decode_all(&mut Concord4Codec, &bytes);
+    assert!(
+      matches!(
+        decoded.as_slice(),
+        [RecvMessage::Corrupt { .. }, RecvMessage::EqptListDone]
+      ),
+      "{:?}",
+      decoded
+    );
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn unanswered_send_is_resent_after_the_response_timeout() {
+    let (client, panel) = tokio::io::duplex(1024);
+    let config = SerialConfig {
+      auto_bootstrap: false,
+      ..SerialConfig::default()
+    };
+    let timeout = config.response_timeout;
+
+    let mut serial = Serial::new(Box::new(client), config);
+    let (outgoing, pending) = Outgoing::new(SendableMessage::DynamicDataRefresh);
+    serial.tx.send(outgoing).await.expect("could not queue message");
+    tokio::spawn(async move { while serial.next().await.is_some() {} });
+
+    let mut panel = Framed::new(panel, PanelCodec);
+    let first = panel.next().await;
+    assert!(
+      matches!(first, Some(Ok(Ok(SendableMessage::DynamicDataRefresh)))),
+      "{:?}",
+      first
+    );
+    let sent = Instant::now();
+    let wall = std::time::Instant::now();
+
+    // no ACK, so the same message goes out again once the paused clock passes the response timeout, \
+    // without waiting for the real one
+    let resent = panel.next().await;
+    assert!(
+      matches!(resent, Some(Ok(Ok(SendableMessage::DynamicDataRefresh)))),
+      "{:?}",
+      resent
+    );
+    assert!(sent.elapsed() >= timeout, "resent after {:?}", sent.elapsed());
+    assert!(
+      wall.elapsed() < timeout,
+      "resent after {:?} of real time",
+      wall.elapsed()
+    );
+
+    panel.send(RecvMessage::Ack).await.expect("could not send ACK");
+    assert_eq!(pending.await, SendOutcome::Acked);
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn exhausted_retries_abandon_the_message() {
+    let (client, panel) = tokio::io::duplex(1024);
+    let config = SerialConfig {
+      auto_bootstrap: false,
+      max_retries: 1,
+      ..SerialConfig::default()
+    };
+
+    let mut serial = Serial::new(Box::new(client), config);
+    let (outgoing, mut pending) = Outgoing::new(SendableMessage::DynamicDataRefresh);
+    serial.tx.send(outgoing).await.expect("could not queue message");
+
+    // the panel never answers
+    let _panel = panel;
+    let outcome = loop {
+      tokio::select! {
+        outcome = &mut pending => break outcome,
+        _ = serial.next() => {}
+      }
+    };
+
+    assert_eq!(outcome, SendOutcome::RetriesExhausted);
+    assert!(!serial.sending);
+    assert!(serial.last_message.is_none(), "{:?}", serial.last_message);
+  }
 }
@@ -0,0 +1,473 @@
+use std::{
+  collections::{HashMap, HashSet},
+  io,
+};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+  io::DuplexStream,
+  sync::mpsc::{self, error::SendError},
+};
+use tokio_util::codec::Decoder;
+
+use crate::{
+  communication::{RecvMessage, SendableMessage},
+  decode,
+  equipment::{
+    AlarmEventData, AlarmTrouble, ArmingLevelData, CodeType, Event, EventSource, Feature, FeatureState, LightsState,
+    PanelData, PanelType, PartitionData, ZoneData, ZoneStatusData, ZoneStatusFlags,
+  },
+  serial::PanelCodec,
+  touchpad::TouchpadDisplay,
+  transport::Transport,
+  ArmingLevel, Keypress, ListRequest,
+};
+
+/// A fake Concord4 panel that speaks the panel side of the SuperBus automation protocol.
+///
+/// It answers equipment list and dynamic data requests, ACKs every frame it receives, \
+/// reacts to arm/disarm/chime/bypass/panic/light keypresses and can be scripted through a [SimulatorHandle].
+///
+/// # example
+/// ```no_run
+/// # use concord4::{Concord4, Simulator};
+/// # async fn example() {
+/// let (transport, panel) = Simulator::new().connect();
+/// let mut client = Concord4::from_transport(transport);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulator {
+  panel: PanelData,
+  partitions: Vec<PartitionData>,
+  zones: Vec<ZoneData>,
+  code: [Keypress; 4],
+  /// the lights that are on, by partition
+  lights: HashMap<u8, HashSet<u8>>,
+}
+
+impl Default for Simulator {
+  fn default() -> Self {
+    Simulator {
+      panel: PanelData {
+        panel_type: PanelType::Concord,
+        hardware_revision: "A1".to_string(),
+        software_revision: "1234".to_string(),
+        serial_number: "12345678".to_string(),
+      },
+      partitions: vec![PartitionData {
+        partition_number: 1,
+        area_number: 0,
+        arming_level: ArmingLevel::Off,
+        zones: HashSet::new(),
+        features: HashSet::new(),
+        last_armed_by: None,
+      }],
+      zones: Vec::new(),
+      code: [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four],
+      lights: HashMap::new(),
+    }
+  }
+}
+
+impl Simulator {
+  /// a disarmed panel with a single partition, no zones and the user code 1234
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// replace the panel type and revision info reported by the simulator
+  pub fn with_panel(mut self, panel: PanelData) -> Self {
+    self.panel = panel;
+    self
+  }
+
+  /// add a partition, replacing any existing partition with the same number
+  pub fn with_partition(mut self, partition: PartitionData) -> Self {
+    self.upsert_partition(partition);
+    self
+  }
+
+  /// add a zone, replacing any existing zone with the same partition and zone number
+  pub fn with_zone(mut self, zone: ZoneData) -> Self {
+    self.upsert_zone(zone);
+    self
+  }
+
+  /// set the user code that arms and disarms the simulator
+  pub fn with_code(mut self, code: [Keypress; 4]) -> Self {
+    self.code = code;
+    self
+  }
+
+  /// run the simulator on a new task over an in-memory pipe
+  ///
+  /// # returns
+  /// the client end of the pipe (to hand to [crate::Concord4::from_transport]) and a [SimulatorHandle]
+  pub fn connect(self) -> (DuplexStream, SimulatorHandle) {
+    let (client, panel) = tokio::io::duplex(4096);
+
+    (client, self.spawn(panel))
+  }
+
+  /// run the simulator on a new task over the given transport
+  pub fn spawn<T: Transport + 'static>(self, transport: T) -> SimulatorHandle {
+    let (tx, rx) = mpsc::channel(32);
+
+    tokio::spawn(async move {
+      if let Err(err) = self.run(transport, rx).await {
+        tracing::error!(target: "concord4::simulator", "simulator stopped: {:?}", err);
+      }
+    });
+
+    SimulatorHandle { tx }
+  }
+
+  async fn run<T: Transport>(mut self, transport: T, mut script: mpsc::Receiver<RecvMessage>) -> io::Result<()> {
+    let mut framed = PanelCodec.framed(transport);
+
+    // like a real panel coming up, ask the client to (re)build its image
+    framed.send(RecvMessage::ClearImage(vec![])).await?;
+
+    loop {
+      tokio::select! {
+        message = framed.next() => match message {
+          Some(Ok(Ok(SendableMessage::Ack))) | Some(Ok(Ok(SendableMessage::Nak))) => {}
+          Some(Ok(Ok(message))) => {
+            tracing::debug!(target: "concord4::simulator", "received: {:?}", message);
+            framed.send(RecvMessage::Ack).await?;
+
+            for reply in self.respond(message) {
+              framed.send(reply).await?;
+            }
+          }
+          Some(Ok(Err(err))) => {
+            tracing::warn!(target: "concord4::simulator", "could not decode message: {}", err);
+            framed.send(RecvMessage::Nak).await?;
+          }
+          Some(Err(err)) => return Err(err),
+          None => return Ok(()),
+        },
+        Some(message) = script.recv() => {
+          self.apply(&message);
+          framed.send(message).await?;
+        },
+      }
+    }
+  }
+
+  fn respond(&mut self, message: SendableMessage) -> Vec<RecvMessage> {
+    match message {
+      SendableMessage::List(ListRequest::AllData) => {
+        let mut replies = vec![RecvMessage::PanelType(self.panel.clone())];
+        replies.extend(self.zones.iter().cloned().map(RecvMessage::ZoneData));
+        replies.extend(self.partitions.iter().cloned().map(RecvMessage::PartitionData));
+        replies.push(RecvMessage::EqptListDone);
+
+        replies
+      }
+      SendableMessage::List(ListRequest::ZoneData) => self.zones.iter().cloned().map(RecvMessage::ZoneData).collect(),
+      SendableMessage::List(ListRequest::PartData) => self
+        .partitions
+        .iter()
+        .cloned()
+        .map(RecvMessage::PartitionData)
+        .collect(),
+      SendableMessage::DynamicDataRefresh => {
+        let mut replies: Vec<RecvMessage> = self
+          .partitions
+          .iter()
+          .map(|partition| {
+            RecvMessage::ArmingLevel(ArmingLevelData {
+              partition_number: partition.partition_number,
+              area_number: partition.area_number,
+              user: partition.last_armed_by.unwrap_or(CodeType::System),
+              arming_level: partition.arming_level,
+            })
+          })
+          .collect();
+        replies.extend(self.zones.iter().map(|zone| RecvMessage::ZoneStatus(zone_status(zone))));
+        replies.extend(
+          self
+            .partitions
+            .iter()
+            .map(|partition| RecvMessage::LightsState(self.lights_state(partition))),
+        );
+
+        replies
+      }
+      SendableMessage::Keypress(partition, keys) => self.keypress(partition, &keys),
+      _ => vec![],
+    }
+  }
+
+  fn keypress(&mut self, partition_number: u8, keys: &[Keypress]) -> Vec<RecvMessage> {
+    let keys: Vec<u8> = keys.iter().copied().map(u8::from).collect();
+    let code: Vec<u8> = self.code.iter().copied().map(u8::from).collect();
+
+    let Some(area_number) = self.partition(partition_number).map(|partition| partition.area_number) else {
+      return vec![];
+    };
+    let display = |text: &str| RecvMessage::Touchpad(touchpad(partition_number, area_number, text));
+
+    // stay is 2 and away is 3, optionally prefixed with 5 for silent and suffixed with 4 for instant
+    let (level, entered) = match keys.as_slice() {
+      [0x05, level @ (0x02 | 0x03), entered @ ..] | [level @ (0x02 | 0x03), entered @ ..] => {
+        (ArmingLevel::from(*level), entered)
+      }
+      [0x01, entered @ ..] => (ArmingLevel::Off, entered),
+      [0x07, 0x01] => {
+        let Some(partition) = self.partition_mut(partition_number) else {
+          return vec![];
+        };
+
+        let chime = !partition.features.remove(&Feature::Chime);
+        if chime {
+          partition.features.insert(Feature::Chime);
+        }
+
+        return vec![
+          display(if chime { "CHIME ON" } else { "CHIME OFF" }),
+          RecvMessage::FeatState(FeatureState {
+            partition_number,
+            area_number,
+            features: partition.features.clone(),
+          }),
+        ];
+      }
+      // a panic key pressed twice raises the matching panic alarm
+      [panic @ 0x0C..=0x0E, again] if panic == again => {
+        let alarm = match panic {
+          0x0C => AlarmEventData::PolicePanic,
+          0x0D => AlarmEventData::AuxiliaryPanic,
+          _ => AlarmEventData::FirePanic,
+        };
+
+        return vec![RecvMessage::AlarmTrouble(AlarmTrouble {
+          partition_number,
+          area_number,
+          // the touchpad the panic came from
+          source_type: EventSource::BusDevice,
+          source_number: (0, 0, 0),
+          event: Event::Alarm(alarm),
+        })];
+      }
+      // lights on or off followed by the light number, where 0 is every light
+      [switch @ (0x10 | 0x11), light @ 0..=9] => {
+        let lights = self.lights.entry(partition_number).or_default();
+        let switched = match light {
+          0 => LightsState::LIGHTS.to_vec(),
+          light => vec![*light],
+        };
+
+        for light in switched {
+          if *switch == 0x10 {
+            lights.insert(light);
+          } else {
+            lights.remove(&light);
+          }
+        }
+
+        return vec![RecvMessage::LightsState(LightsState {
+          partition_number,
+          area_number,
+          lights: lights.clone(),
+        })];
+      }
+      // # 7, the code, the zone number and # again toggles the zone's bypass
+      [0x0B, 0x07, entered @ .., 0x0B] => {
+        if entered.get(..code.len()) != Some(code.as_slice()) {
+          return vec![display("INVALID CODE")];
+        }
+
+        let zone_number = entered[code.len()..]
+          .iter()
+          .fold(0u16, |number, digit| number * 10 + u16::from(*digit));
+        let Some(zone) = self
+          .zones
+          .iter_mut()
+          .find(|zone| zone.partition_number == partition_number && zone.zone_number == zone_number)
+        else {
+          return vec![display("INVALID ZONE")];
+        };
+
+        zone.zone_status.bypassed = !zone.zone_status.bypassed;
+
+        return vec![
+          display(if zone.zone_status.bypassed {
+            "BYPASSED"
+          } else {
+            "UNBYPASS"
+          }),
+          RecvMessage::ZoneStatus(zone_status(zone)),
+        ];
+      }
+      _ => return vec![],
+    };
+
+    if entered.get(..code.len()) != Some(code.as_slice()) {
+      return vec![display("INVALID CODE")];
+    }
+
+    let mut replies = vec![];
+
+    // disarming also clears any zones left in alarm
+    if level == ArmingLevel::Off {
+      for zone in self.zones.iter_mut() {
+        if zone.partition_number == partition_number && zone.zone_status.alarm {
+          zone.zone_status.alarm = false;
+          replies.push(RecvMessage::ZoneStatus(zone_status(zone)));
+        }
+      }
+    }
+
+    // there is only the one code, and it belongs to the first user
+    let user = CodeType::User(0);
+    if let Some(partition) = self.partition_mut(partition_number) {
+      partition.arming_level = level;
+      partition.last_armed_by = Some(user);
+    }
+
+    replies.push(RecvMessage::ArmingLevel(ArmingLevelData {
+      partition_number,
+      area_number,
+      user,
+      arming_level: level,
+    }));
+    replies.push(display(&format!("ARMING LEVEL {}", u8::from(level))));
+
+    replies
+  }
+
+  /// keep the simulated panel consistent with messages injected by a script
+  fn apply(&mut self, message: &RecvMessage) {
+    match message {
+      RecvMessage::ZoneData(data) => self.upsert_zone(data.clone()),
+      RecvMessage::PartitionData(data) => self.upsert_partition(data.clone()),
+      RecvMessage::ZoneStatus(data) => {
+        if let Some(zone) = self
+          .zones
+          .iter_mut()
+          .find(|zone| zone.partition_number == data.partition_number && zone.zone_number == data.zone_number)
+        {
+          zone.zone_status = data.zone_status;
+        }
+      }
+      RecvMessage::ArmingLevel(data) => {
+        if let Some(partition) = self.partition_mut(data.partition_number) {
+          partition.arming_level = data.arming_level;
+          partition.last_armed_by = Some(data.user);
+        }
+      }
+      RecvMessage::FeatState(data) => {
+        if let Some(partition) = self.partition_mut(data.partition_number) {
+          partition.features = data.features.clone();
+        }
+      }
+      RecvMessage::LightsState(data) => {
+        self.lights.insert(data.partition_number, data.lights.clone());
+      }
+      _ => {}
+    }
+  }
+
+  fn lights_state(&self, partition: &PartitionData) -> LightsState {
+    LightsState {
+      partition_number: partition.partition_number,
+      area_number: partition.area_number,
+      lights: self
+        .lights
+        .get(&partition.partition_number)
+        .cloned()
+        .unwrap_or_default(),
+    }
+  }
+
+  fn upsert_partition(&mut self, partition: PartitionData) {
+    self
+      .partitions
+      .retain(|existing| existing.partition_number != partition.partition_number);
+    self.partitions.push(partition);
+  }
+
+  fn upsert_zone(&mut self, zone: ZoneData) {
+    self.zones.retain(|existing| {
+      existing.partition_number != zone.partition_number || existing.zone_number != zone.zone_number
+    });
+    self.zones.push(zone);
+  }
+
+  fn partition(&self, partition_number: u8) -> Option<&PartitionData> {
+    self
+      .partitions
+      .iter()
+      .find(|partition| partition.partition_number == partition_number)
+  }
+
+  fn partition_mut(&mut self, partition_number: u8) -> Option<&mut PartitionData> {
+    self
+      .partitions
+      .iter_mut()
+      .find(|partition| partition.partition_number == partition_number)
+  }
+}
+
+/// A handle for scripting a running [Simulator].
+///
+/// Every method sends a message from the panel to the client, exactly as if the panel had produced it.
+#[derive(Debug, Clone)]
+pub struct SimulatorHandle {
+  tx: mpsc::Sender<RecvMessage>,
+}
+
+impl SimulatorHandle {
+  /// send any message from the simulated panel
+  ///
+  /// zone and partition messages also update the simulator's own view of the panel
+  pub async fn inject(&self, message: RecvMessage) -> Result<(), SendError<RecvMessage>> {
+    self.tx.send(message).await
+  }
+
+  /// report an alarm or trouble event
+  pub async fn alarm_trouble(&self, event: AlarmTrouble) -> Result<(), SendError<RecvMessage>> {
+    self.inject(RecvMessage::AlarmTrouble(event)).await
+  }
+
+  /// change the status of a zone, e.g. to trip it or put it into alarm
+  pub async fn set_zone_status(
+    &self,
+    partition_number: u8,
+    zone_number: u16,
+    zone_status: impl Into<ZoneStatusFlags>,
+  ) -> Result<(), SendError<RecvMessage>> {
+    self
+      .inject(RecvMessage::ZoneStatus(ZoneStatusData {
+        partition_number,
+        area_number: 0,
+        zone_number,
+        zone_status: zone_status.into(),
+      }))
+      .await
+  }
+}
+
+fn zone_status(zone: &ZoneData) -> ZoneStatusData {
+  ZoneStatusData {
+    partition_number: zone.partition_number,
+    area_number: zone.area_number,
+    zone_number: zone.zone_number,
+    zone_status: zone.zone_status,
+  }
+}
+
+fn touchpad(partition_number: u8, area_number: u8, text: &str) -> TouchpadDisplay {
+  let display_tokens = decode::encode_text_tokens(text);
+
+  TouchpadDisplay {
+    partition_number,
+    area_number,
+    message_type: 0,
+    text: decode::decode_text_tokens(&display_tokens),
+    display_tokens,
+  }
+}
@@ -1,8 +1,13 @@
 use dashmap::DashMap;
 use std::{
   collections::HashSet,
-  sync::{Arc, OnceLock},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, RwLock,
+  },
+  time::{Duration, SystemTime},
 };
+use tokio::time::Instant;
 
 #[cfg(feature = "json")]
 use serde::Serialize;
@@ -10,19 +15,43 @@ use serde::Serialize;
 use crate::{
   communication::RecvMessage,
   equipment::{
-    ArmingLevelData, IntIdentifiable, PanelData, PartitionData, StringIdentifiable, ZoneData, ZoneStatusData,
+    AlarmTrouble, ArmingLevel, ArmingLevelData, CodeType, ConditionChange, ConditionKind, ConditionSource,
+    DelayDirection, DelayExtension, DelayPhase, EntryExitDelay, Event, Feature, FeatureState, IntIdentifiable,
+    LightsState, PanelData, PartitionData, StringIdentifiable, UserLights, ZoneData, ZoneStatusData, ZoneStatusFlags,
   },
 };
 
 pub type WrappedState = Arc<ConcordState>;
 
-#[derive(Debug, Clone, Default)]
-pub struct WrappedPanel(pub OnceLock<PanelData>);
+/// The panel's type and revisions, replaced each time the panel lists them.
+#[derive(Debug, Default)]
+pub struct WrappedPanel(pub RwLock<Option<PanelData>>);
+
+impl WrappedPanel {
+  /// the panel, once it has been listed
+  pub fn get(&self) -> Option<PanelData> {
+    self.0.read().ok().and_then(|panel| panel.clone())
+  }
+
+  /// replace the panel, returning the one it replaced
+  fn set(&self, panel: PanelData) -> Option<PanelData> {
+    match self.0.write() {
+      Ok(mut slot) => slot.replace(panel),
+      Err(poisoned) => poisoned.into_inner().replace(panel),
+    }
+  }
+}
+
+impl Clone for WrappedPanel {
+  fn clone(&self) -> Self {
+    Self(RwLock::new(self.get()))
+  }
+}
 
 #[cfg(feature = "json")]
 impl Serialize for WrappedPanel {
   fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-    if let Some(panel) = self.0.get() {
+    if let Some(panel) = self.get() {
       panel.serialize(serializer)
     } else {
       PanelData::default().serialize(serializer)
@@ -30,6 +59,48 @@ impl Serialize for WrappedPanel {
   }
 }
 
+#[derive(Debug, Default)]
+pub struct WrappedFlag(pub AtomicBool);
+
+impl Clone for WrappedFlag {
+  fn clone(&self) -> Self {
+    Self(AtomicBool::new(self.0.load(Ordering::Relaxed)))
+  }
+}
+
+/// The equipment the panel has listed since a re-sync began, so whatever it no longer has can be dropped once it's done.
+#[derive(Debug, Clone, Default)]
+struct Listed {
+  zones: HashSet<String>,
+  partitions: HashSet<u8>,
+  groups: HashSet<String>,
+}
+
+#[derive(Debug, Default)]
+struct WrappedResync(Mutex<Option<Listed>>);
+
+impl WrappedResync {
+  fn with<T>(&self, f: impl FnOnce(&mut Option<Listed>) -> T) -> T {
+    match self.0.lock() {
+      Ok(mut listed) => f(&mut listed),
+      Err(poisoned) => f(&mut poisoned.into_inner()),
+    }
+  }
+}
+
+impl Clone for WrappedResync {
+  fn clone(&self) -> Self {
+    Self(Mutex::new(self.with(|listed| listed.clone())))
+  }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for WrappedFlag {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bool(self.0.load(Ordering::Relaxed))
+  }
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct Group {
@@ -54,6 +125,203 @@ impl StringIdentifiable for Group {
   }
 }
 
+/// An entry or exit delay that is counting down on a partition.
+#[derive(Debug, Clone)]
+pub struct DelayCountdown {
+  pub direction: DelayDirection,
+  pub extension: DelayExtension,
+  /// how long the delay lasts in total
+  pub seconds: u16,
+  area_number: u8,
+  started: Instant,
+}
+
+impl DelayCountdown {
+  fn new(delay: &EntryExitDelay) -> Self {
+    Self {
+      direction: delay.direction,
+      extension: delay.extension,
+      seconds: delay.seconds,
+      area_number: delay.area_number,
+      started: Instant::now(),
+    }
+  }
+
+  /// the end of this delay, as the panel would have reported it
+  fn ended(&self, partition_number: u8) -> EntryExitDelay {
+    EntryExitDelay {
+      partition_number,
+      area_number: self.area_number,
+      direction: self.direction,
+      extension: self.extension,
+      phase: DelayPhase::End,
+      seconds: self.seconds,
+    }
+  }
+
+  /// how long is left before the delay runs out, zero once it has
+  pub fn remaining(&self) -> Duration {
+    Duration::from_secs(self.seconds.into()).saturating_sub(self.started.elapsed())
+  }
+
+  /// the whole seconds left, rounded up so the countdown only reads 0 once it has run out
+  pub fn remaining_secs(&self) -> u16 {
+    let remaining = self.remaining();
+    let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+    secs.min(self.seconds.into()) as u16
+  }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for DelayCountdown {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    let mut countdown = serializer.serialize_struct("DelayCountdown", 4)?;
+    countdown.serialize_field("direction", &self.direction)?;
+    countdown.serialize_field("extension", &self.extension)?;
+    countdown.serialize_field("seconds", &self.seconds)?;
+    countdown.serialize_field("remaining", &self.remaining_secs())?;
+    countdown.end()
+  }
+}
+
+/// What a partition's siren is doing, following the setup, go and stop messages the panel sends to sirens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase", tag = "state"))]
+pub enum SirenState {
+  /// no siren has been set up since the client connected
+  #[default]
+  Idle,
+  /// the panel told the siren what to play and will start it with the next go
+  Armed { repetitions: u8, cadence: u32 },
+  /// the siren is playing its cadence
+  Sounding { repetitions: u8, cadence: u32 },
+  /// the panel stopped the siren
+  Stopped,
+}
+
+/// An alarm or trouble the panel has reported and not yet cancelled or restored.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct ActiveCondition {
+  pub kind: ConditionKind,
+  pub source: ConditionSource,
+  pub partition_number: u8,
+  /// the event that raised the condition
+  pub event: Event,
+  /// when the client first saw the condition
+  pub since: SystemTime,
+}
+
+impl ActiveCondition {
+  fn new(kind: ConditionKind, data: AlarmTrouble) -> Self {
+    Self {
+      kind,
+      source: data.source(),
+      partition_number: data.partition_number,
+      event: data.event,
+      since: SystemTime::now(),
+    }
+  }
+}
+
+impl StringIdentifiable for ActiveCondition {
+  fn id(&self) -> String {
+    condition_id(&self.source, self.kind, &self.event)
+  }
+}
+
+// a restoral has a different general type than the event it restores, so only the kind and specific type are matched
+fn condition_id(source: &ConditionSource, kind: ConditionKind, event: &Event) -> String {
+  let kind = match kind {
+    ConditionKind::Alarm => "a",
+    ConditionKind::FireTrouble => "f",
+    ConditionKind::NonfireTrouble => "n",
+    ConditionKind::SystemTrouble => "s",
+  };
+
+  format!("{}-{}{}", source.id(), kind, event.specific_type())
+}
+
+/// One second of an entry or exit delay counting down, see [crate::Concord4Handle::delay_ticks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct DelayTick {
+  pub partition: u8,
+  pub direction: DelayDirection,
+  /// whole seconds left, see [DelayCountdown::remaining_secs]
+  pub remaining: u16,
+}
+
+/// A change to the [ConcordState], published after the message that caused it has been applied.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum StateChange {
+  /// the transport to the panel closed; the state is stale until the panel has been listed again
+  Disconnected,
+  /// a new transport was opened after a disconnect; conditions, delays, sirens and lights were cleared \
+  /// and the state is being refreshed from the panel
+  Connected,
+  /// the panel reported its type and revisions for the first time, or again while re-syncing
+  PanelIdentified(PanelData),
+  /// a zone was seen for the first time
+  ZoneAdded(ZoneData),
+  /// a partition was seen for the first time
+  PartitionAdded(PartitionData),
+  /// a zone the panel didn't list again when re-syncing was dropped
+  ZoneRemoved(ZoneData),
+  /// a partition the panel didn't list again when re-syncing was dropped
+  PartitionRemoved(PartitionData),
+  /// a known zone changed status
+  #[cfg_attr(feature = "json", serde(rename_all = "camelCase"))]
+  ZoneStatusChanged {
+    zone_id: String,
+    old: ZoneStatusFlags,
+    new: ZoneStatusFlags,
+  },
+  /// a known partition changed arming level
+  #[cfg_attr(feature = "json", serde(rename_all = "camelCase"))]
+  ArmingLevelChanged {
+    partition: u8,
+    old: ArmingLevel,
+    new: ArmingLevel,
+    /// who changed the arming level, when the panel reports it
+    user: Option<CodeType>,
+  },
+  /// a known partition had features (chime, quick arm, ...) turned on or off
+  #[cfg_attr(feature = "json", serde(rename_all = "camelCase"))]
+  FeaturesChanged {
+    partition: u8,
+    old: HashSet<Feature>,
+    new: HashSet<Feature>,
+  },
+  /// a partition's siren was set up, started or stopped
+  #[cfg_attr(feature = "json", serde(rename_all = "camelCase"))]
+  SirenChanged {
+    partition: u8,
+    old: SirenState,
+    new: SirenState,
+  },
+  /// an entry or exit delay started counting down
+  DelayStarted(EntryExitDelay),
+  /// an entry or exit delay ended, because the panel said so, because it ran out \
+  /// or because the partition's arming level changed (other than to the arming that started an exit delay)
+  DelayEnded(EntryExitDelay),
+  /// lights on a partition were turned on or off
+  #[cfg_attr(feature = "json", serde(rename_all = "camelCase"))]
+  LightsChanged {
+    partition: u8,
+    old: HashSet<u8>,
+    new: HashSet<u8>,
+  },
+  /// the panel reported a new alarm or trouble
+  ConditionRaised(ActiveCondition),
+  /// an active alarm or trouble was cancelled or restored
+  ConditionCleared(ActiveCondition),
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "json", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct ConcordState {
@@ -61,6 +329,18 @@ pub struct ConcordState {
   pub zones: DashMap<String, ZoneData>,
   pub partitions: DashMap<u8, PartitionData>,
   pub groups: DashMap<String, Group>,
+  /// the entry or exit delay counting down on each partition, if any
+  pub delays: DashMap<u8, DelayCountdown>,
+  /// the siren on each partition that has had one set up
+  pub sirens: DashMap<u8, SirenState>,
+  /// the lights (1-9) that are on in each partition that has reported its lights
+  pub lights: DashMap<u8, HashSet<u8>>,
+  /// every active alarm and trouble, by [ActiveCondition]'s id
+  pub conditions: DashMap<String, ActiveCondition>,
+  /// set while the connection is down, since nothing here is being kept up to date
+  pub stale: WrappedFlag,
+  #[cfg_attr(feature = "json", serde(skip))]
+  resync: WrappedResync,
 }
 
 impl ConcordState {
@@ -69,19 +349,120 @@ impl ConcordState {
     serde_json::to_string(&self)
   }
 
-  pub(crate) fn handle_result(&self, data: RecvMessage) {
+  /// whether the state may be out of date because the connection to the panel was lost
+  pub fn is_stale(&self) -> bool {
+    self.stale.0.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn set_stale(&self, stale: bool) {
+    self.stale.0.store(stale, Ordering::Relaxed);
+  }
+
+  /// forget everything the panel only reports as it happens (alarms, troubles, delays, sirens and lights)
+  ///
+  /// anything that changed while the connection was down was missed, and the automation image listed on reconnect \
+  /// only brings the panel, zones and partitions back up to date (see [ConcordState::begin_resync])
+  pub(crate) fn clear_transient(&self) {
+    self.delays.clear();
+    self.sirens.clear();
+    self.lights.clear();
+    self.conditions.clear();
+  }
+
+  /// start over on the equipment list: once the panel has listed everything again, \
+  /// the zones, partitions and groups it left out are dropped
+  pub(crate) fn begin_resync(&self) {
+    self.resync.with(|listed| *listed = Some(Listed::default()));
+  }
+
+  /// drop the equipment the panel didn't list again since [ConcordState::begin_resync]
+  ///
+  /// # returns
+  /// a [StateChange::ZoneRemoved] or [StateChange::PartitionRemoved] for each one dropped
+  fn finish_resync(&self) -> Vec<StateChange> {
+    let Some(listed) = self.resync.with(Option::take) else {
+      return Vec::new();
+    };
+
+    let mut changes = Vec::new();
+
+    let zones: Vec<_> = self
+      .zones
+      .iter()
+      .filter(|zone| !listed.zones.contains(zone.key()))
+      .map(|zone| zone.key().clone())
+      .collect();
+    for (zone_id, zone) in zones.into_iter().filter_map(|zone_id| self.zones.remove(&zone_id)) {
+      tracing::debug!(target: "concord4::state::resync", "dropping zone the panel no longer lists: {}", zone_id);
+
+      self.partitions.entry(zone.partition_number).and_modify(|partition| {
+        partition.zones.remove(&zone_id);
+      });
+      for mut group in self.groups.iter_mut() {
+        group.zones.remove(&zone_id);
+      }
+
+      changes.push(StateChange::ZoneRemoved(zone));
+    }
+
+    self.groups.retain(|group_id, _| listed.groups.contains(group_id));
+
+    let partitions: Vec<_> = self
+      .partitions
+      .iter()
+      .filter(|partition| !listed.partitions.contains(partition.key()))
+      .map(|partition| *partition.key())
+      .collect();
+    for (number, partition) in partitions
+      .into_iter()
+      .filter_map(|number| self.partitions.remove(&number))
+    {
+      tracing::debug!(target: "concord4::state::resync", "dropping partition the panel no longer lists: {}", number);
+
+      changes.push(StateChange::PartitionRemoved(partition));
+    }
+
+    changes
+  }
+
+  /// every active alarm and trouble, oldest first
+  pub fn active_conditions(&self) -> Vec<ActiveCondition> {
+    let mut conditions: Vec<_> = self.conditions.iter().map(|condition| condition.clone()).collect();
+    conditions.sort_by_key(|condition| condition.since);
+
+    conditions
+  }
+
+  /// the active alarms and troubles on a partition, including its zones and devices, oldest first
+  pub fn partition_conditions(&self, partition: u8) -> Vec<ActiveCondition> {
+    let mut conditions = self.active_conditions();
+    conditions.retain(|condition| condition.partition_number == partition);
+
+    conditions
+  }
+
+  /// apply a message to the state
+  ///
+  /// # returns
+  /// every [StateChange] the message caused
+  pub(crate) fn handle_result(&self, data: RecvMessage) -> Vec<StateChange> {
+    let mut changes = self.expire_delays();
+
     match data {
       RecvMessage::Ack => {}
       RecvMessage::Nak => {}
-      RecvMessage::PanelType(data) => self.handle_panel_type(data),
-      RecvMessage::ZoneData(data) => self.handle_zone_data(data),
-      RecvMessage::ZoneStatus(data) => self.handle_zone_status(data),
-      RecvMessage::PartitionData(data) => self.handle_partition_data(data),
-      RecvMessage::ArmingLevel(data) => self.handle_arming_level(data),
+      RecvMessage::PanelType(data) => changes.extend(self.handle_panel_type(data)),
+      RecvMessage::ZoneData(data) => changes.extend(self.handle_zone_data(data)),
+      RecvMessage::ZoneStatus(data) => changes.extend(self.handle_zone_status(data)),
+      RecvMessage::PartitionData(data) => changes.extend(self.end_superseded_delay(self.handle_partition_data(data))),
+      RecvMessage::ArmingLevel(data) => changes.extend(self.end_superseded_delay(self.handle_arming_level(data))),
       RecvMessage::EqptListDone => {
-        tracing::trace!(target: "concord4::state::eqpt-list-done", "unhandled: {:?}", data);
+        tracing::debug!(target: "concord4::state::eqpt-list-done", "equipment list complete, state is current");
+        changes.extend(self.finish_resync());
+        self.set_stale(false);
       }
       RecvMessage::SirenSync => {
+        // only keeps several sirens playing in step, which doesn't change what they're doing
         tracing::trace!(target: "concord4::state::siren-sync", "unhandled: {:?}", data);
       }
       RecvMessage::Touchpad(_) => {
@@ -112,53 +493,62 @@ impl ConcordState {
         tracing::trace!(target: "concord4::state::light-attach", "unhandled: {:?}", data);
       }
       RecvMessage::ClearImage(_) => {
-        tracing::trace!(target: "concord4::state::clear-image", "unhandled: {:?}", data);
-      }
-      RecvMessage::AlarmTrouble(_) => {
-        tracing::trace!(target: "concord4::state::alarm-trouble", "unhandled: {:?}", data);
-      }
-      RecvMessage::EntryExitDelay(_) => {
-        tracing::trace!(target: "concord4::state::entry-exit-delay", "unhandled: {:?}", data);
-      }
-      RecvMessage::SirenSetup(_) => {
-        tracing::trace!(target: "concord4::state::siren-setup", "unhandled: {:?}", data);
-      }
-      RecvMessage::SirenGo => {
-        tracing::trace!(target: "concord4::state::siren-go", "unhandled: {:?}", data);
-      }
-      RecvMessage::SirenStop(_) => {
-        tracing::trace!(target: "concord4::state::siren-stop", "unhandled: {:?}", data);
-      }
-      RecvMessage::FeatState(_) => {
-        tracing::trace!(target: "concord4::state::feat-state", "unhandled: {:?}", data);
+        tracing::debug!(target: "concord4::state::clear-image", "panel is clearing its image, re-syncing equipment");
+        self.begin_resync();
       }
+      RecvMessage::AlarmTrouble(data) => changes.extend(self.handle_alarm_trouble(data)),
+      RecvMessage::EntryExitDelay(data) => changes.extend(self.handle_entry_exit_delay(data)),
+      RecvMessage::SirenSetup(data) => changes.extend(self.set_siren(
+        data.partition_number,
+        SirenState::Armed {
+          repetitions: data.repetitions,
+          cadence: data.cadence,
+        },
+      )),
+      RecvMessage::SirenGo => changes.extend(self.handle_siren_go()),
+      RecvMessage::SirenStop(data) => changes.extend(self.set_siren(data.partition_number, SirenState::Stopped)),
+      RecvMessage::FeatState(data) => changes.extend(self.handle_feature_state(data)),
       RecvMessage::Temp(_) => {
         tracing::trace!(target: "concord4::state::temp", "unhandled: {:?}", data);
       }
       RecvMessage::TimeAndDate(_) => {
         tracing::trace!(target: "concord4::state::time-and-date", "unhandled: {:?}", data);
       }
-      RecvMessage::LightsState(_) => {
-        tracing::trace!(target: "concord4::state::lights-state", "unhandled: {:?}", data);
-      }
-      RecvMessage::UserLights(_) => {
-        tracing::trace!(target: "concord4::state::user-lights", "unhandled: {:?}", data);
-      }
+      RecvMessage::LightsState(data) => changes.extend(self.set_lights(data.partition_number, data.lights)),
+      RecvMessage::UserLights(data) => changes.extend(self.handle_user_lights(data)),
       RecvMessage::Keyfob(_) => {
         tracing::trace!(target: "concord4::state::keyfob", "unhandled: {:?}", data);
       }
+      RecvMessage::Unknown { .. } => {
+        tracing::debug!(target: "concord4::state::unknown", "unhandled: {:?}", data);
+      }
+      RecvMessage::Corrupt { .. } => {
+        tracing::debug!(target: "concord4::state::corrupt", "unhandled: {:?}", data);
+      }
     };
+
+    changes
   }
 
-  fn handle_panel_type(&self, data: PanelData) {
+  fn handle_panel_type(&self, data: PanelData) -> Option<StateChange> {
     tracing::debug!(target: "concord4::state::panel-type", "setting panel: {:?}", data);
 
-    let _ = self.panel.0.set(data);
+    let resyncing = self.resync.with(|listed| listed.is_some());
+    let old = self.panel.set(data.clone());
+
+    (old.is_none() || resyncing).then_some(StateChange::PanelIdentified(data))
   }
 
-  fn handle_zone_data(&self, data: ZoneData) {
+  fn handle_zone_data(&self, data: ZoneData) -> Option<StateChange> {
     tracing::debug!(target: "concord4::state::zone-data", "updating zone: {:?}", data);
 
+    self.resync.with(|listed| {
+      if let Some(listed) = listed {
+        listed.zones.insert(data.id());
+        listed.groups.insert(data.group_id());
+      }
+    });
+
     self.partitions.entry(data.partition_number).and_modify(|partition| {
       partition.zones.insert(data.id());
     });
@@ -172,20 +562,41 @@ impl ConcordState {
       }
     }
 
-    self.zones.insert(data.id(), data);
+    match self.zones.insert(data.id(), data.clone()) {
+      Some(_) => None,
+      None => Some(StateChange::ZoneAdded(data)),
+    }
   }
 
-  fn handle_zone_status(&self, data: ZoneStatusData) {
+  fn handle_zone_status(&self, data: ZoneStatusData) -> Option<StateChange> {
     tracing::debug!(target: "concord4::state::zone-status", "updating zone status: {:?}", data);
 
+    let mut change = None;
+
     self.zones.entry(data.zone_id()).and_modify(|zone| {
+      if zone.zone_status != data.zone_status {
+        change = Some(StateChange::ZoneStatusChanged {
+          zone_id: zone.id(),
+          old: zone.zone_status,
+          new: data.zone_status,
+        });
+      }
+
       zone.zone_status = data.zone_status;
     });
+
+    change
   }
 
-  fn handle_partition_data(&self, mut data: PartitionData) {
+  fn handle_partition_data(&self, mut data: PartitionData) -> Option<StateChange> {
     tracing::debug!(target: "concord4::state::partition-data", "updating partition: {:?}", data);
 
+    self.resync.with(|listed| {
+      if let Some(listed) = listed {
+        listed.partitions.insert(data.id());
+      }
+    });
+
     data.zones = self
       .zones
       .iter()
@@ -198,14 +609,453 @@ impl ConcordState {
       })
       .collect();
 
-    self.partitions.insert(data.id(), data);
+    // partition data doesn't carry the features or who armed it, so keep what the other messages said
+    if let Some(partition) = self.partitions.get(&data.id()) {
+      data.features = partition.features.clone();
+      data.last_armed_by = partition.last_armed_by;
+    }
+
+    match self.partitions.insert(data.id(), data.clone()) {
+      Some(old) if old.arming_level != data.arming_level => Some(StateChange::ArmingLevelChanged {
+        partition: data.partition_number,
+        old: old.arming_level,
+        new: data.arming_level,
+        user: None,
+      }),
+      Some(_) => None,
+      None => Some(StateChange::PartitionAdded(data)),
+    }
   }
 
-  fn handle_arming_level(&self, data: ArmingLevelData) {
+  fn handle_arming_level(&self, data: ArmingLevelData) -> Option<StateChange> {
     tracing::debug!(target: "concord4::state::arming-level", "updating arming level: {:?}", data);
 
+    let mut change = None;
+
     self.partitions.entry(data.partition_number).and_modify(|partition| {
+      if partition.arming_level != data.arming_level {
+        change = Some(StateChange::ArmingLevelChanged {
+          partition: data.partition_number,
+          old: partition.arming_level,
+          new: data.arming_level,
+          user: Some(data.user),
+        });
+      }
+
       partition.arming_level = data.arming_level;
+      partition.last_armed_by = Some(data.user);
+    });
+
+    change
+  }
+
+  fn handle_entry_exit_delay(&self, data: EntryExitDelay) -> Option<StateChange> {
+    tracing::debug!(target: "concord4::state::entry-exit-delay", "updating delay: {:?}", data);
+
+    match data.phase {
+      DelayPhase::Start => {
+        self.delays.insert(data.partition_number, DelayCountdown::new(&data));
+        Some(StateChange::DelayStarted(data))
+      }
+      DelayPhase::End => self
+        .delays
+        .remove(&data.partition_number)
+        .map(|_| StateChange::DelayEnded(data)),
+    }
+  }
+
+  /// end the delays that have run out, in case the panel's end frame was lost
+  ///
+  /// # returns
+  /// a [StateChange::DelayEnded] for each delay that was still counting down past its end
+  pub(crate) fn expire_delays(&self) -> Vec<StateChange> {
+    let expired: Vec<_> = self
+      .delays
+      .iter()
+      .filter(|delay| delay.remaining().is_zero())
+      .map(|delay| *delay.key())
+      .collect();
+
+    expired
+      .into_iter()
+      .filter_map(|partition| {
+        self
+          .delays
+          .remove_if(&partition, |_, delay| delay.remaining().is_zero())
+      })
+      .map(|(partition, delay)| {
+        tracing::debug!(target: "concord4::state::entry-exit-delay", "partition {} delay ran out", partition);
+        StateChange::DelayEnded(delay.ended(partition))
+      })
+      .collect()
+  }
+
+  // disarming cancels an entry or exit delay, and arming ends an entry delay (the alarm goes off or it's rearmed), \
+  // but an exit delay starts with the arming level change that arms the partition
+  fn end_superseded_delay(&self, change: Option<StateChange>) -> Vec<StateChange> {
+    let Some(change) = change else {
+      return Vec::new();
+    };
+
+    let ended = match change {
+      StateChange::ArmingLevelChanged { partition, new, .. } => self
+        .delays
+        .remove_if(&partition, |_, delay| {
+          new == ArmingLevel::Off || delay.direction == DelayDirection::Entry
+        })
+        .map(|(partition, delay)| StateChange::DelayEnded(delay.ended(partition))),
+      _ => None,
+    };
+
+    std::iter::once(change).chain(ended).collect()
+  }
+
+  fn set_siren(&self, partition: u8, state: SirenState) -> Option<StateChange> {
+    tracing::debug!(target: "concord4::state::siren", "partition {} siren: {:?}", partition, state);
+
+    let old = self.sirens.insert(partition, state).unwrap_or_default();
+
+    (old != state).then_some(StateChange::SirenChanged {
+      partition,
+      old,
+      new: state,
+    })
+  }
+
+  fn handle_siren_go(&self) -> Vec<StateChange> {
+    // go isn't addressed to a partition, it starts every siren that was set up
+    let armed: Vec<_> = self
+      .sirens
+      .iter()
+      .filter_map(|siren| match *siren {
+        SirenState::Armed { repetitions, cadence } => Some((*siren.key(), repetitions, cadence)),
+        _ => None,
+      })
+      .collect();
+
+    armed
+      .into_iter()
+      .filter_map(|(partition, repetitions, cadence)| {
+        self.set_siren(partition, SirenState::Sounding { repetitions, cadence })
+      })
+      .collect()
+  }
+
+  fn set_lights(&self, partition: u8, lights: HashSet<u8>) -> Option<StateChange> {
+    tracing::debug!(target: "concord4::state::lights", "partition {} lights on: {:?}", partition, lights);
+
+    let old = self.lights.insert(partition, lights.clone()).unwrap_or_default();
+
+    (old != lights).then_some(StateChange::LightsChanged {
+      partition,
+      old,
+      new: lights,
+    })
+  }
+
+  fn handle_user_lights(&self, data: UserLights) -> Option<StateChange> {
+    let mut lights = self
+      .lights
+      .get(&data.partition_number)
+      .map(|lights| lights.clone())
+      .unwrap_or_default();
+
+    // light 0 switches every light at once
+    let switched = match data.light_number {
+      0 => LightsState::LIGHTS.to_vec(),
+      light => vec![light],
+    };
+
+    for light in switched {
+      if data.on {
+        lights.insert(light);
+      } else {
+        lights.remove(&light);
+      }
+    }
+
+    self.set_lights(data.partition_number, lights)
+  }
+
+  fn handle_alarm_trouble(&self, data: AlarmTrouble) -> Option<StateChange> {
+    tracing::debug!(target: "concord4::state::alarm-trouble", "alarm or trouble: {:?}", data);
+
+    let (kind, change) = data.event.condition()?;
+    let id = condition_id(&data.source(), kind, &data.event);
+
+    match change {
+      // a repeat of an active condition keeps the time it was first seen
+      ConditionChange::Raised => match self.conditions.entry(id) {
+        dashmap::Entry::Occupied(_) => None,
+        dashmap::Entry::Vacant(entry) => {
+          let condition = ActiveCondition::new(kind, data);
+          entry.insert(condition.clone());
+          Some(StateChange::ConditionRaised(condition))
+        }
+      },
+      ConditionChange::Cleared => self
+        .conditions
+        .remove(&id)
+        .map(|(_, condition)| StateChange::ConditionCleared(condition)),
+    }
+  }
+
+  fn handle_feature_state(&self, data: FeatureState) -> Option<StateChange> {
+    tracing::debug!(target: "concord4::state::feat-state", "updating features: {:?}", data);
+
+    let mut change = None;
+
+    self.partitions.entry(data.partition_number).and_modify(|partition| {
+      if partition.features != data.features {
+        change = Some(StateChange::FeaturesChanged {
+          partition: data.partition_number,
+          old: partition.features.clone(),
+          new: data.features.clone(),
+        });
+      }
+
+      partition.features = data.features;
     });
+
+    change
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    equipment::{AlarmEventData, EventSource, FireEventData, SystemTroubleEventData},
+    fixtures::{alarm_trouble, arming_level},
+  };
+
+  fn partition(state: &ConcordState, arming_level: ArmingLevel) {
+    state.handle_result(RecvMessage::PartitionData(PartitionData {
+      partition_number: 1,
+      area_number: 0,
+      arming_level,
+      zones: HashSet::new(),
+      features: HashSet::new(),
+      last_armed_by: None,
+    }));
+  }
+
+  fn delay(direction: DelayDirection, phase: DelayPhase, seconds: u16) -> RecvMessage {
+    RecvMessage::EntryExitDelay(EntryExitDelay {
+      partition_number: 1,
+      area_number: 0,
+      direction,
+      extension: DelayExtension::Standard,
+      phase,
+      seconds,
+    })
+  }
+
+  fn delays_ended(changes: &[StateChange]) -> usize {
+    changes
+      .iter()
+      .filter(|change| matches!(change, StateChange::DelayEnded(delay) if delay.phase == DelayPhase::End))
+      .count()
+  }
+
+  #[test]
+  fn end_frame_ends_delay() {
+    let state = ConcordState::default();
+    partition(&state, ArmingLevel::Away);
+
+    state.handle_result(delay(DelayDirection::Entry, DelayPhase::Start, 30));
+    assert!(state.delays.contains_key(&1));
+
+    let changes = state.handle_result(delay(DelayDirection::Entry, DelayPhase::End, 30));
+    assert_eq!(delays_ended(&changes), 1);
+    assert!(state.delays.is_empty());
+  }
+
+  #[test]
+  fn disarming_ends_delay() {
+    for direction in [DelayDirection::Entry, DelayDirection::Exit] {
+      let state = ConcordState::default();
+      partition(&state, ArmingLevel::Away);
+      state.handle_result(delay(direction, DelayPhase::Start, 30));
+
+      let changes = state.handle_result(arming_level(1, ArmingLevel::Off));
+      assert!(matches!(changes[0], StateChange::ArmingLevelChanged { .. }));
+      assert_eq!(delays_ended(&changes), 1);
+      assert!(state.delays.is_empty());
+    }
+  }
+
+  #[test]
+  fn arming_keeps_exit_delay() {
+    let state = ConcordState::default();
+    partition(&state, ArmingLevel::Off);
+    state.handle_result(delay(DelayDirection::Exit, DelayPhase::Start, 30));
+
+    let changes = state.handle_result(arming_level(1, ArmingLevel::Away));
+    assert_eq!(delays_ended(&changes), 0);
+    assert!(state.delays.contains_key(&1));
+  }
+
+  #[test]
+  fn delay_expires_without_end_frame() {
+    let state = ConcordState::default();
+    state.handle_result(delay(DelayDirection::Entry, DelayPhase::Start, 0));
+    assert!(state.delays.contains_key(&1));
+
+    assert_eq!(delays_ended(&state.expire_delays()), 1);
+    assert!(state.delays.is_empty());
+    assert!(state.expire_delays().is_empty());
+  }
+
+  #[test]
+  fn running_delay_does_not_expire() {
+    let state = ConcordState::default();
+    state.handle_result(delay(DelayDirection::Exit, DelayPhase::Start, 30));
+
+    assert!(state.expire_delays().is_empty());
+    assert!(state.delays.contains_key(&1));
+  }
+
+  #[test]
+  fn conditions_raise_and_clear() {
+    let table = [
+      (
+        EventSource::Zone,
+        Event::Alarm(AlarmEventData::Fire),
+        Event::AlarmCancel(AlarmEventData::Fire),
+      ),
+      (
+        EventSource::Zone,
+        Event::Alarm(AlarmEventData::PolicePanic),
+        Event::AlarmRestoral(AlarmEventData::PolicePanic),
+      ),
+      (
+        EventSource::Zone,
+        Event::FireTrouble(FireEventData::LowBattery),
+        Event::FireTroubleRestoral(FireEventData::LowBattery),
+      ),
+      (
+        EventSource::Zone,
+        Event::NonfireTrouble(FireEventData::Supervisory),
+        Event::NonfireTroubleRestoral(FireEventData::Supervisory),
+      ),
+      (
+        EventSource::System,
+        Event::SystemTrouble(SystemTroubleEventData::MainAcFailure),
+        Event::SystemTroubleRestoral(SystemTroubleEventData::MainAcFailure),
+      ),
+    ];
+
+    for (source_type, raised, cleared) in table {
+      let state = ConcordState::default();
+      let (kind, _) = raised.condition().unwrap();
+
+      let changes = state.handle_result(alarm_trouble(1, source_type.clone(), raised.clone()));
+      assert!(
+        matches!(&changes[..], [StateChange::ConditionRaised(condition)] if condition.kind == kind),
+        "{:?}",
+        raised
+      );
+      assert_eq!(state.partition_conditions(1).len(), 1);
+
+      // a repeat keeps the condition raised without another change
+      assert!(state
+        .handle_result(alarm_trouble(1, source_type.clone(), raised.clone()))
+        .is_empty());
+
+      let changes = state.handle_result(alarm_trouble(1, source_type.clone(), cleared.clone()));
+      assert!(
+        matches!(&changes[..], [StateChange::ConditionCleared(condition)] if condition.kind == kind),
+        "{:?}",
+        cleared
+      );
+      assert!(state.active_conditions().is_empty());
+    }
+  }
+
+  #[test]
+  fn restoral_only_clears_its_own_condition() {
+    let state = ConcordState::default();
+    state.handle_result(alarm_trouble(1, EventSource::Zone, Event::Alarm(AlarmEventData::Fire)));
+    state.handle_result(alarm_trouble(
+      1,
+      EventSource::Zone,
+      Event::FireTrouble(FireEventData::LowBattery),
+    ));
+
+    let changes = state.handle_result(alarm_trouble(
+      1,
+      EventSource::Zone,
+      Event::AlarmRestoral(AlarmEventData::PolicePanic),
+    ));
+    assert!(changes.is_empty());
+
+    state.handle_result(alarm_trouble(
+      1,
+      EventSource::Zone,
+      Event::AlarmCancel(AlarmEventData::Fire),
+    ));
+    let remaining = state.active_conditions();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].kind, ConditionKind::FireTrouble);
+  }
+
+  fn zone(zone_number: u16, group_number: u8) -> RecvMessage {
+    RecvMessage::ZoneData(ZoneData {
+      partition_number: 1,
+      area_number: 0,
+      group_number,
+      zone_number,
+      zone_type: crate::equipment::ZoneType::Hardwired,
+      zone_status: ZoneStatusFlags::default(),
+      zone_text: String::new(),
+    })
+  }
+
+  #[test]
+  fn resync_replaces_the_panel_and_drops_unlisted_equipment() {
+    let state = ConcordState::default();
+    let panel = PanelData::default();
+
+    let changes = state.handle_result(RecvMessage::PanelType(panel.clone()));
+    assert!(
+      matches!(&changes[..], [StateChange::PanelIdentified(_)]),
+      "{:?}",
+      changes
+    );
+    assert!(state.handle_result(RecvMessage::PanelType(panel.clone())).is_empty());
+
+    partition(&state, ArmingLevel::Off);
+    state.handle_result(zone(5, 10));
+    state.handle_result(zone(6, 11));
+    state.handle_result(RecvMessage::EqptListDone);
+
+    state.handle_result(RecvMessage::ClearImage(vec![0x00]));
+    let changes = state.handle_result(RecvMessage::PanelType(PanelData {
+      software_revision: "2.0".to_string(),
+      ..panel
+    }));
+    assert!(
+      matches!(&changes[..], [StateChange::PanelIdentified(_)]),
+      "{:?}",
+      changes
+    );
+    assert_eq!(state.panel.get().unwrap().software_revision, "2.0");
+
+    partition(&state, ArmingLevel::Off);
+    state.handle_result(zone(5, 10));
+    let changes = state.handle_result(RecvMessage::EqptListDone);
+    assert!(
+      matches!(&changes[..], [StateChange::ZoneRemoved(zone)] if zone.zone_number == 6),
+      "{:?}",
+      changes
+    );
+    assert!(state.zones.contains_key("p1-z5"));
+    assert!(!state.zones.contains_key("p1-z6"));
+    assert!(!state.groups.contains_key("p1-g11"));
+    assert!(!state.partitions.get(&1).unwrap().zones.contains("p1-z6"));
+
+    // a list that isn't part of a re-sync drops nothing
+    assert!(state.handle_result(RecvMessage::EqptListDone).is_empty());
   }
 }
@@ -0,0 +1,94 @@
+use std::{
+  fmt,
+  pin::Pin,
+  task::{ready, Context, Poll},
+};
+
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::ReusableBoxFuture;
+
+/// A subscriber fell too far behind and some messages were dropped before it could see them.
+///
+/// The subscription keeps going with the oldest message still buffered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("subscriber lagged behind and missed {0} messages")]
+pub struct Lagged(pub u64);
+
+/// A stream of everything published on a connection, independent of every other subscriber.
+///
+/// Yields [Err] with [Lagged] when messages were dropped because this subscriber was too slow, \
+/// and ends once the connection is closed.
+///
+/// # example
+/// ```no_run
+/// # use concord4::*;
+/// # use futures::StreamExt;
+/// # async fn example(client: Concord4Handle) {
+/// let mut messages = client.subscribe();
+/// while let Some(message) = messages.next().await {
+///   match message {
+///     Ok(message) => println!("{:?}", message),
+///     Err(Lagged(missed)) => println!("missed {} messages", missed),
+///   }
+/// }
+/// # }
+/// ```
+pub struct Subscription<T> {
+  inner: ReusableBoxFuture<'static, (Result<T, RecvError>, broadcast::Receiver<T>)>,
+}
+
+impl<T: Clone + Send + 'static> Subscription<T> {
+  pub(crate) fn new(rx: broadcast::Receiver<T>) -> Self {
+    Self {
+      inner: ReusableBoxFuture::new(next_message(rx)),
+    }
+  }
+
+  /// a subscription to a connection that has already closed
+  pub(crate) fn closed() -> Self {
+    let (_, rx) = broadcast::channel(1);
+
+    Self::new(rx)
+  }
+
+  /// receive the next message
+  ///
+  /// # returns
+  /// an [Option] containing an [Ok] with the next message, \
+  /// an [Option] containing an [Err] with [Lagged] if messages were missed, \
+  /// or [None] if the connection was closed
+  pub async fn recv(&mut self) -> Option<Result<T, Lagged>> {
+    self.next().await
+  }
+}
+
+async fn next_message<T: Clone>(mut rx: broadcast::Receiver<T>) -> (Result<T, RecvError>, broadcast::Receiver<T>) {
+  let result = rx.recv().await;
+
+  (result, rx)
+}
+
+impl<T: Clone + Send + 'static> Stream for Subscription<T> {
+  type Item = Result<T, Lagged>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let (result, rx) = ready!(self.inner.poll(cx));
+    self.inner.set(next_message(rx));
+
+    match result {
+      Ok(message) => Poll::Ready(Some(Ok(message))),
+      Err(RecvError::Lagged(missed)) => {
+        tracing::warn!(target: "concord4::subscription", "subscriber missed {} messages", missed);
+        Poll::Ready(Some(Err(Lagged(missed))))
+      }
+      Err(RecvError::Closed) => Poll::Ready(None),
+    }
+  }
+}
+
+impl<T> fmt::Debug for Subscription<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Subscription").finish_non_exhaustive()
+  }
+}
@@ -1,4 +1,4 @@
-use crate::decode;
+use crate::{decode, DecodeError};
 
 #[cfg(feature = "json")]
 use serde::Serialize;
@@ -13,14 +13,40 @@ pub struct TouchpadDisplay {
   pub text: String,
 }
 
-impl From<Vec<u8>> for TouchpadDisplay {
-  fn from(data: Vec<u8>) -> Self {
-    TouchpadDisplay {
+/// what the panel shows when it refuses keypresses, as opposed to status lines like "AC POWER FAILURE"
+const REJECTIONS: [&str; 2] = ["INVALID CODE", "INVALID ZONE"];
+
+impl TouchpadDisplay {
+  /// whether the display is the panel refusing a command, e.g. "INVALID CODE" after a bad arm/disarm attempt
+  pub fn is_rejection(&self) -> bool {
+    // word tokens decode with a trailing space, so compare the words rather than the raw text
+    let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    REJECTIONS.iter().any(|rejection| text.contains(rejection))
+  }
+}
+
+impl TryFrom<Vec<u8>> for TouchpadDisplay {
+  type Error = DecodeError;
+
+  fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+    DecodeError::check_len(&data, 3)?;
+
+    Ok(TouchpadDisplay {
       partition_number: data[0],
       area_number: data[1],
       message_type: data[2],
       display_tokens: data[3..].to_vec(),
       text: decode::decode_text_tokens(&data[3..]),
-    }
+    })
+  }
+}
+
+impl From<TouchpadDisplay> for Vec<u8> {
+  fn from(value: TouchpadDisplay) -> Self {
+    let mut data = vec![value.partition_number, value.area_number, value.message_type];
+    data.extend(value.display_tokens);
+
+    data
   }
 }
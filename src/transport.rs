@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use tokio::{
+  io::{AsyncRead, AsyncWrite},
+  net::{TcpStream, ToSocketAddrs},
+};
+use tokio_serial::{SerialPort, SerialPortBuilderExt, SerialStream};
+
+use crate::{consts, ClientError};
+
+/// A bidirectional byte stream that the SuperBus automation protocol can be spoken over.
+///
+/// This is implemented for anything that is [AsyncRead] + [AsyncWrite] + [Send] + [Unpin], \
+/// so a tty, a socket or an in-memory pipe can all be handed to [crate::Concord4::from_transport].
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + ?Sized> Transport for T {}
+
+pub(crate) type BoxedTransport = Box<dyn Transport>;
+
+/// how to set up a local serial port, set through [crate::Concord4Builder]
+#[derive(Debug, Clone)]
+pub(crate) struct PortConfig {
+  pub baud_rate: u32,
+  pub parity: tokio_serial::Parity,
+  pub clear_buffer: bool,
+}
+
+impl Default for PortConfig {
+  fn default() -> Self {
+    Self {
+      baud_rate: consts::BAUD_RATE,
+      parity: consts::PARITY,
+      clear_buffer: true,
+    }
+  }
+}
+
+pub(crate) fn open_serial(path: &str, config: &PortConfig) -> Result<SerialStream, ClientError> {
+  let port = tokio_serial::new(path, config.baud_rate)
+    .data_bits(consts::DATA_BITS)
+    .parity(config.parity)
+    .timeout(Duration::from_millis(10))
+    .open_native_async()?;
+
+  tracing::info!("Receiving data on {} at {} baud:", path, config.baud_rate);
+
+  if config.clear_buffer {
+    // must clear buffer because system won't know which message the ACKs are referring to
+    port.clear(tokio_serial::ClearBuffer::All)?;
+  }
+
+  Ok(port)
+}
+
+pub(crate) async fn connect_tcp<A: ToSocketAddrs>(addr: A) -> Result<TcpStream, ClientError> {
+  let stream = TcpStream::connect(addr).await.map_err(ClientError::Transport)?;
+
+  // frames are tiny and the panel is waiting on every ACK, so don't let nagle hold them back
+  stream.set_nodelay(true).map_err(ClientError::Transport)?;
+
+  if let Ok(peer) = stream.peer_addr() {
+    tracing::info!("Receiving data from {} over tcp:", peer);
+  }
+
+  Ok(stream)
+}
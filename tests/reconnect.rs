@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use concord4::*;
+use futures::{SinkExt, StreamExt};
+use tokio::{io::DuplexStream, sync::mpsc};
+use tokio_util::codec::Framed;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+type Panel = Framed<DuplexStream, PanelCodec>;
+
+/// a supervised client over in-memory pipes, with the panel end of every pipe it opens
+async fn connect() -> (Concord4Handle, mpsc::UnboundedReceiver<Panel>) {
+  let (opened, panels) = mpsc::unbounded_channel();
+
+  let client = Concord4::builder()
+    // keep the first connection quiet, the bootstrap under test is the one after reconnecting
+    .bootstrap_delay(Duration::from_secs(3600))
+    .reconnect(ReconnectPolicy {
+      initial_delay: Duration::from_millis(10),
+      ..Default::default()
+    })
+    .connect_with(move || {
+      let opened = opened.clone();
+
+      async move {
+        let (client, panel) = tokio::io::duplex(4096);
+        let _ = opened.send(Framed::new(panel, PanelCodec));
+
+        Ok(client)
+      }
+    })
+    .await
+    .expect("could not connect");
+
+  (client.spawn(), panels)
+}
+
+async fn next_change(changes: &mut Subscription<StateChange>) -> StateChange {
+  tokio::time::timeout(WAIT, changes.recv())
+    .await
+    .expect("no change published")
+    .expect("subscription closed")
+    .expect("subscription lagged")
+}
+
+/// the next message from the client that isn't an ACK of something the panel sent
+async fn next_request(panel: &mut Panel) -> SendableMessage {
+  loop {
+    let request = tokio::time::timeout(WAIT, panel.next())
+      .await
+      .expect("no request received")
+      .expect("client hung up")
+      .expect("could not read request")
+      .expect("could not decode request");
+
+    if !matches!(request, SendableMessage::Ack) {
+      return request;
+    }
+  }
+}
+
+#[tokio::test]
+async fn reconnect_marks_stale_clears_and_bootstraps_again() {
+  let (client, mut panels) = connect().await;
+  let mut changes = client.subscribe_changes();
+  let mut panel = panels.recv().await.expect("no pipe opened");
+
+  panel
+    .send(RecvMessage::AlarmTrouble(AlarmTrouble {
+      partition_number: 1,
+      area_number: 0,
+      source_type: EventSource::Zone,
+      source_number: (0, 0, 5),
+      event: Event::Alarm(AlarmEventData::Fire),
+    }))
+    .await
+    .expect("could not send alarm");
+  assert!(matches!(
+    next_change(&mut changes).await,
+    StateChange::ConditionRaised(_)
+  ));
+
+  drop(panel);
+
+  let change = next_change(&mut changes).await;
+  assert!(matches!(change, StateChange::Disconnected), "{:?}", change);
+  let change = next_change(&mut changes).await;
+  assert!(matches!(change, StateChange::Connected), "{:?}", change);
+
+  // nothing has been listed yet, and the alarm may have been cancelled while the panel was unreachable
+  assert!(client.state.is_stale());
+  assert!(client.state.active_conditions().is_empty());
+
+  let mut panel = tokio::time::timeout(WAIT, panels.recv())
+    .await
+    .expect("no reconnect attempted")
+    .expect("no pipe opened");
+
+  let request = next_request(&mut panel).await;
+  assert!(
+    matches!(request, SendableMessage::List(ListRequest::AllData)),
+    "{:?}",
+    request
+  );
+  panel.send(RecvMessage::Ack).await.expect("could not send ACK");
+  panel
+    .send(RecvMessage::EqptListDone)
+    .await
+    .expect("could not send list done");
+
+  let request = next_request(&mut panel).await;
+  assert!(matches!(request, SendableMessage::DynamicDataRefresh), "{:?}", request);
+  assert!(!client.state.is_stale());
+}
@@ -0,0 +1,309 @@
+use std::{collections::HashSet, future::Future, time::Duration};
+
+use concord4::*;
+
+const WAIT: Duration = Duration::from_secs(5);
+const CODE: [Keypress; 4] = [Keypress::One, Keypress::Two, Keypress::Three, Keypress::Four];
+const WRONG_CODE: [Keypress; 4] = [Keypress::Four, Keypress::Three, Keypress::Two, Keypress::One];
+
+fn simulator() -> Simulator {
+  Simulator::new().with_zone(ZoneData {
+    partition_number: 1,
+    area_number: 0,
+    group_number: 10,
+    zone_number: 5,
+    zone_type: ZoneType::Hardwired,
+    zone_status: ZoneStatusFlags::default(),
+    zone_text: "FRONT DOOR".to_string(),
+  })
+}
+
+/// a client driven on its own task, once it has loaded the simulator's equipment list
+async fn connect(simulator: Simulator) -> (Concord4Handle, SimulatorHandle) {
+  let (transport, panel) = simulator.connect();
+  let client = Concord4::from_transport(transport).spawn();
+
+  eventually(|| async {
+    client.state.panel.get().is_some() && client.state.zones.contains_key("p1-z5") && !client.state.is_stale()
+  })
+  .await;
+
+  (client, panel)
+}
+
+async fn eventually<F, Fut>(condition: F)
+where
+  F: Fn() -> Fut,
+  Fut: Future<Output = bool>,
+{
+  tokio::time::timeout(WAIT, async {
+    while !condition().await {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  })
+  .await
+  .expect("condition never held");
+}
+
+fn arm(mode: ArmMode, code: [Keypress; 4]) -> ArmOptions {
+  ArmOptions {
+    mode,
+    code,
+    level: None,
+    partition: Some(1),
+  }
+}
+
+#[tokio::test]
+async fn bootstrap_loads_the_equipment_list() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let panel = client.state.panel.get().expect("panel not loaded");
+  assert_eq!(panel.serial_number, "12345678");
+
+  let partition = client.state.partitions.get(&1).expect("partition not loaded");
+  assert_eq!(partition.arming_level, ArmingLevel::Off);
+  drop(partition);
+
+  let zone = client.state.zones.get("p1-z5").expect("zone not loaded");
+  assert_eq!(zone.zone_text, "FRONT DOOR");
+}
+
+#[tokio::test]
+async fn arms_and_disarms_with_the_right_code() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .arm_and_confirm(arm(ArmMode::Away, CODE), WAIT)
+    .await
+    .expect("could not arm");
+  assert!(
+    matches!(outcome, ConfirmOutcome::Confirmed(ArmingLevel::Away)),
+    "{:?}",
+    outcome
+  );
+  eventually(|| async { client.state.partitions.get(&1).unwrap().arming_level == ArmingLevel::Away }).await;
+
+  let outcome = client
+    .disarm_and_confirm(
+      DisarmOptions {
+        code: CODE,
+        partition: Some(1),
+      },
+      WAIT,
+    )
+    .await
+    .expect("could not disarm");
+  assert!(
+    matches!(outcome, ConfirmOutcome::Confirmed(ArmingLevel::Off)),
+    "{:?}",
+    outcome
+  );
+}
+
+#[tokio::test]
+async fn arms_while_driving_the_connection() {
+  let (transport, _panel) = simulator().connect();
+  let mut client = Concord4::from_transport(transport);
+
+  tokio::time::timeout(WAIT, async {
+    while !client.state.partitions.contains_key(&1) || client.state.is_stale() {
+      client
+        .recv()
+        .await
+        .expect("connection closed")
+        .expect("could not receive");
+    }
+  })
+  .await
+  .expect("equipment list never loaded");
+
+  let outcome = client
+    .drive_arm_and_confirm(arm(ArmMode::Away, CODE), WAIT)
+    .await
+    .expect("could not arm");
+  assert!(
+    matches!(outcome, ConfirmOutcome::Confirmed(ArmingLevel::Away)),
+    "{:?}",
+    outcome
+  );
+  assert_eq!(client.state.partitions.get(&1).unwrap().arming_level, ArmingLevel::Away);
+}
+
+#[tokio::test]
+async fn stay_arms_to_home() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .arm_and_confirm(arm(ArmMode::Stay, CODE), WAIT)
+    .await
+    .expect("could not arm");
+  assert!(
+    matches!(outcome, ConfirmOutcome::Confirmed(ArmingLevel::Home)),
+    "{:?}",
+    outcome
+  );
+}
+
+#[tokio::test]
+async fn wrong_code_is_rejected() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .arm_and_confirm(arm(ArmMode::Away, WRONG_CODE), WAIT)
+    .await
+    .expect("could not arm");
+  let ConfirmOutcome::Rejected(display) = outcome else {
+    panic!("arming with the wrong code was {:?}", outcome);
+  };
+  assert_eq!(display.text.trim(), "INVALID CODE");
+
+  let outcome = client
+    .disarm_and_confirm(
+      DisarmOptions {
+        code: WRONG_CODE,
+        partition: Some(1),
+      },
+      WAIT,
+    )
+    .await
+    .expect("could not disarm");
+  assert!(matches!(outcome, ConfirmOutcome::Rejected(_)), "{:?}", outcome);
+  assert_eq!(client.state.partitions.get(&1).unwrap().arming_level, ArmingLevel::Off);
+}
+
+#[tokio::test]
+async fn toggles_the_chime() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .toggle_chime(Some(1))
+    .await
+    .expect("could not toggle chime")
+    .timeout(WAIT)
+    .await;
+  assert_eq!(outcome, SendOutcome::Acked);
+  eventually(|| async {
+    client
+      .state
+      .partitions
+      .get(&1)
+      .unwrap()
+      .features
+      .contains(&Feature::Chime)
+  })
+  .await;
+
+  let outcome = client
+    .toggle_chime(Some(1))
+    .await
+    .expect("could not toggle chime")
+    .timeout(WAIT)
+    .await;
+  assert_eq!(outcome, SendOutcome::Acked);
+  eventually(|| async { client.state.partitions.get(&1).unwrap().features == HashSet::new() }).await;
+}
+
+#[tokio::test]
+async fn bypasses_and_unbypasses_a_zone() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .bypass_zone_and_confirm("p1-z5", CODE, WAIT)
+    .await
+    .expect("could not bypass zone");
+  let ConfirmOutcome::Confirmed(flags) = outcome else {
+    panic!("bypass was {:?}", outcome);
+  };
+  assert!(flags.bypassed);
+
+  let bypassed = client.bypass_zone("p1-z5", CODE).await;
+  assert!(matches!(bypassed, Err(ClientError::ZoneBypassed(_))), "{:?}", bypassed);
+
+  let outcome = client
+    .unbypass_zone_and_confirm("p1-z5", CODE, WAIT)
+    .await
+    .expect("could not unbypass zone");
+  assert!(
+    matches!(
+      outcome,
+      ConfirmOutcome::Confirmed(ZoneStatusFlags { bypassed: false, .. })
+    ),
+    "{:?}",
+    outcome
+  );
+}
+
+#[tokio::test]
+async fn bypass_with_the_wrong_code_is_rejected() {
+  let (client, _panel) = connect(simulator()).await;
+
+  let outcome = client
+    .bypass_zone_and_confirm("p1-z5", WRONG_CODE, WAIT)
+    .await
+    .expect("could not bypass zone");
+  assert!(matches!(outcome, ConfirmOutcome::Rejected(_)), "{:?}", outcome);
+  assert!(!client.state.zones.get("p1-z5").unwrap().zone_status.bypassed);
+}
+
+#[tokio::test]
+async fn injected_alarms_reach_the_client() {
+  let (client, panel) = connect(simulator()).await;
+  let mut messages = client.subscribe();
+
+  panel
+    .alarm_trouble(AlarmTrouble {
+      partition_number: 1,
+      area_number: 0,
+      source_type: EventSource::Zone,
+      source_number: (0, 0, 5),
+      event: Event::Alarm(AlarmEventData::Police),
+    })
+    .await
+    .expect("simulator stopped");
+
+  let alarm = tokio::time::timeout(WAIT, async {
+    loop {
+      if let Some(Ok(RecvMessage::AlarmTrouble(alarm))) = messages.recv().await {
+        break alarm;
+      }
+    }
+  })
+  .await
+  .expect("alarm never arrived");
+  assert!(
+    matches!(alarm.event, Event::Alarm(AlarmEventData::Police)),
+    "{:?}",
+    alarm
+  );
+}
+
+#[tokio::test]
+async fn handles_taken_before_the_journal_record_to_it() {
+  let (transport, panel) = simulator().connect();
+  let client = Concord4::from_transport(transport);
+  let early = client.handle();
+  let _client = client.with_journal(MemoryJournal::new(10)).spawn();
+
+  panel
+    .alarm_trouble(AlarmTrouble {
+      partition_number: 1,
+      area_number: 0,
+      source_type: EventSource::Zone,
+      source_number: (0, 0, 5),
+      event: Event::Alarm(AlarmEventData::Police),
+    })
+    .await
+    .expect("simulator stopped");
+
+  let journal = early.journal().expect("journal not attached");
+  eventually(|| async {
+    let alarms = journal
+      .query_async(JournalQuery::new().kind(JournalKind::Alarm))
+      .await
+      .expect("could not read journal");
+
+    alarms.len() == 1
+  })
+  .await;
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use concord4::*;
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_util::codec::Framed;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+/// a client connected to a fake adapter, with the panel end of the socket
+async fn connect() -> (Concord4, Framed<tokio::net::TcpStream, PanelCodec>) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.expect("could not bind listener");
+  let addr = listener.local_addr().expect("listener has no address");
+
+  let (client, accepted) = tokio::join!(
+    Concord4::builder().auto_bootstrap(false).connect_tcp(addr),
+    listener.accept()
+  );
+  let client = client.expect("could not connect to listener");
+  let (socket, _) = accepted.expect("could not accept connection");
+
+  (client, Framed::new(socket, PanelCodec))
+}
+
+#[tokio::test]
+async fn receives_and_acks_a_frame() {
+  let (mut client, mut panel) = connect().await;
+
+  panel
+    .send(RecvMessage::ArmingLevel(ArmingLevelData {
+      partition_number: 1,
+      area_number: 0,
+      user: CodeType::User(0),
+      arming_level: ArmingLevel::Home,
+    }))
+    .await
+    .expect("could not send frame");
+
+  let message = tokio::time::timeout(WAIT, client.recv())
+    .await
+    .expect("no message received");
+  assert!(
+    matches!(
+      message,
+      Some(Ok(RecvMessage::ArmingLevel(ArmingLevelData {
+        partition_number: 1,
+        arming_level: ArmingLevel::Home,
+        ..
+      })))
+    ),
+    "{:?}",
+    message
+  );
+
+  let reply = tokio::time::timeout(WAIT, panel.next())
+    .await
+    .expect("no reply received");
+  assert!(matches!(reply, Some(Ok(Ok(SendableMessage::Ack)))), "{:?}", reply);
+}
+
+#[tokio::test]
+async fn send_resolves_when_the_panel_acks() {
+  let (client, mut panel) = connect().await;
+  let client = client.spawn();
+
+  let pending = client
+    .send(SendableMessage::DynamicDataRefresh)
+    .await
+    .expect("could not queue message");
+
+  let request = tokio::time::timeout(WAIT, panel.next())
+    .await
+    .expect("no request received");
+  assert!(
+    matches!(request, Some(Ok(Ok(SendableMessage::DynamicDataRefresh)))),
+    "{:?}",
+    request
+  );
+
+  panel.send(RecvMessage::Ack).await.expect("could not send ACK");
+  assert_eq!(pending.timeout(WAIT).await, SendOutcome::Acked);
+}
+
+#[tokio::test]
+async fn send_resolves_when_the_socket_drops() {
+  let (client, mut panel) = connect().await;
+  let client = client.spawn();
+
+  let pending = client
+    .send(SendableMessage::DynamicDataRefresh)
+    .await
+    .expect("could not queue message");
+
+  let request = tokio::time::timeout(WAIT, panel.next())
+    .await
+    .expect("no request received");
+  assert!(request.is_some(), "socket closed before the request arrived");
+
+  drop(panel);
+  assert_eq!(pending.timeout(WAIT).await, SendOutcome::Disconnected);
+}